@@ -0,0 +1,78 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{analyze_input, parse_input};
+
+// Degrees at least this large are counted together
+const DEGREE_COLUMNS: usize = 8;
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to input directory
+    #[clap(short = 'd', long = "dir", default_value = "in")]
+    dir: PathBuf,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut files = std::fs::read_dir(&cli.dir)
+        .unwrap_or_else(|_| {
+            eprintln!("no such directory: {}", cli.dir.display());
+            std::process::exit(1)
+        })
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    let deg_header = (1..DEGREE_COLUMNS)
+        .map(|d| format!("deg{}", d))
+        .chain([format!("deg{}+", DEGREE_COLUMNS)])
+        .collect::<Vec<_>>()
+        .join(",");
+    println!(
+        "file,N,M,T,LA,LB,min_deg,max_deg,avg_deg,{},diameter,bridges,articulation_points,avg_edge_length,total_distance,distinct_targets,route_vertices",
+        deg_header
+    );
+    let fmt = |v: Option<usize>| v.map_or(String::new(), |v| v.to_string());
+    for file in files {
+        let name = file.to_string_lossy();
+        let in_file = cli.dir.join(&file);
+        let input_s = std::fs::read_to_string(&in_file).unwrap();
+        let input = match parse_input(&input_s) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("{}: {:#}", name, err);
+                continue;
+            }
+        };
+
+        let stats = analyze_input(&input);
+        let count = |d: usize| stats.degree_counts.get(d).copied().unwrap_or(0);
+        let degs = (1..DEGREE_COLUMNS)
+            .map(count)
+            .chain([(DEGREE_COLUMNS..stats.degree_counts.len()).map(count).sum()])
+            .map(|cnt| cnt.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        println!(
+            "{},{},{},{},{},{},{},{},{:.3},{},{},{},{},{:.3},{},{},{}",
+            name,
+            input.n(),
+            input.m(),
+            input.t_sz(),
+            input.al(),
+            input.bl(),
+            stats.min_degree(),
+            stats.max_degree(),
+            stats.avg_degree(),
+            degs,
+            fmt(stats.diameter),
+            stats.bridges,
+            stats.articulation_points,
+            stats.avg_edge_length,
+            fmt(stats.total_distance),
+            stats.distinct_targets,
+            stats.route_vertices
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{compute_score, copy_lower_bound, parse_input};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to output directory. Scores are reported for the outputs found in it.
+    out_dir: Option<PathBuf>,
+    /// Path to input directory
+    #[clap(short = 'd', long = "dir", default_value = "in")]
+    dir: PathBuf,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut files = std::fs::read_dir(&cli.dir)
+        .unwrap_or_else(|_| {
+            eprintln!("no such directory: {}", cli.dir.display());
+            std::process::exit(1)
+        })
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    println!("file,bound,score,gap");
+    for file in files {
+        let name = file.to_string_lossy();
+        let input_s = std::fs::read_to_string(cli.dir.join(&file)).unwrap();
+        let input = match parse_input(&input_s) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("{}: {:#}", name, err);
+                continue;
+            }
+        };
+
+        let bound = copy_lower_bound(&input);
+        if bound == usize::MAX {
+            eprintln!("{}: some target cannot be reached", name);
+            println!("{},,,", name);
+            continue;
+        }
+
+        let output_s = cli
+            .out_dir
+            .as_ref()
+            .and_then(|dir| std::fs::read_to_string(dir.join(&file)).ok());
+        let score = match output_s.map(|output_s| compute_score(&input, &output_s)) {
+            Some(Ok(score)) => Some(score),
+            Some(Err(err)) => {
+                eprintln!("{}: {:#}", name, err);
+                None
+            }
+            None => None,
+        };
+
+        match score {
+            Some(score) => println!(
+                "{},{},{},{}",
+                name,
+                bound,
+                score,
+                score as i64 - bound as i64
+            ),
+            None => println!("{},{},,", name, bound),
+        }
+    }
+}
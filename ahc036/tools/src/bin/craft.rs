@@ -0,0 +1,35 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::parse_input_dsl;
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to the description of an input (see `parse_input_dsl`)
+    dsl: PathBuf,
+    /// Skip the checks of the generator's guarantees
+    #[clap(long)]
+    unchecked: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let dsl = std::fs::read_to_string(&cli.dsl).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.dsl.display());
+        std::process::exit(1)
+    });
+
+    let res = parse_input_dsl(&dsl).and_then(|builder| {
+        if cli.unchecked {
+            builder.build_unchecked()
+        } else {
+            builder.build()
+        }
+    });
+    match res {
+        Ok(input) => print!("{}", input),
+        Err(err) => {
+            eprintln!("{:#}", err);
+            std::process::exit(1)
+        }
+    }
+}
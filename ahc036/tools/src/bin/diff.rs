@@ -0,0 +1,202 @@
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use tools::{draw_svg, judge_with, parse_input, parse_output, Input, Observer, State, Timeline};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to input file
+    input: PathBuf,
+    /// Path to output file A
+    output_a: PathBuf,
+    /// Path to output file B
+    output_b: PathBuf,
+    /// Write frames of both outputs at matching target indices to the specified file
+    #[clap(long)]
+    html: Option<PathBuf>,
+    /// Interval of target indices drawn in the html
+    #[clap(long, default_value = "50")]
+    stride: usize,
+    /// The number of recent vertices to draw
+    #[clap(short, default_value = "10")]
+    k: usize,
+}
+
+// The turn and the score when each target was reached
+#[derive(Default)]
+struct Segments {
+    turns: Vec<usize>,
+    copies: Vec<usize>,
+}
+
+impl Segments {
+    // (ops, copies) spent between reaching the (i-1)-th target and the i-th target
+    fn get(&self, i: usize) -> Option<(usize, usize)> {
+        let (turn, copies) = (*self.turns.get(i)?, self.copies[i]);
+        if i == 0 {
+            Some((turn, copies))
+        } else {
+            Some((turn - self.turns[i - 1], copies - self.copies[i - 1]))
+        }
+    }
+}
+
+impl Observer for Segments {
+    fn on_target_reached(&mut self, _input: &Input, state: &State, _target_idx: usize) {
+        self.turns.push(state.op_cnt());
+        self.copies.push(state.score());
+    }
+}
+
+struct Side {
+    name: &'static str,
+    initial_a: Vec<usize>,
+    timeline: Timeline,
+    segments: Segments,
+    score: usize,
+}
+
+fn replay(name: &'static str, input: &Input, path: &Path, k: usize) -> Side {
+    let output_s = std::fs::read_to_string(path).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", path.display());
+        std::process::exit(1)
+    });
+    let initial_a = match parse_output(&output_s, input) {
+        Ok(output) => output.initial_a().to_vec(),
+        Err(_) => vec![],
+    };
+
+    let mut timeline = Timeline::new();
+    let mut segments = Segments::default();
+    let score = match judge_with(input, &output_s, k, &mut [&mut timeline, &mut segments]) {
+        Ok(res) => res.score,
+        Err(err) => {
+            println!("{}: {:#}", name, err);
+            0
+        }
+    };
+
+    Side {
+        name,
+        initial_a,
+        timeline,
+        segments,
+        score,
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let input_s = std::fs::read_to_string(&cli.input).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.input.display());
+        std::process::exit(1)
+    });
+    let input = parse_input(&input_s).unwrap_or_else(|err| {
+        eprintln!("{:#}", err);
+        std::process::exit(1)
+    });
+
+    let sides = [
+        replay("A", &input, &cli.output_a, cli.k),
+        replay("B", &input, &cli.output_b, cli.k),
+    ];
+    let [a, b] = &sides;
+
+    for side in sides.iter() {
+        println!(
+            "{}: Score = {}, Targets = {} / {}",
+            side.name,
+            side.score,
+            side.segments.turns.len(),
+            input.t_sz()
+        );
+    }
+
+    let a_diff = (0..a.initial_a.len().max(b.initial_a.len()))
+        .filter(|&i| a.initial_a.get(i) != b.initial_a.get(i))
+        .collect::<Vec<_>>();
+    match a_diff.first() {
+        Some(&i) => println!(
+            "Initial A differs at {} / {} positions, first at {} ({:?} vs {:?})",
+            a_diff.len(),
+            input.al(),
+            i,
+            a.initial_a.get(i),
+            b.initial_a.get(i)
+        ),
+        None => println!("Initial A is the same"),
+    }
+
+    // The first segment spending more operations on one side
+    let first_behind = (0..input.t_sz()).find(|&i| match (a.segments.get(i), b.segments.get(i)) {
+        (Some((ops_a, _)), Some((ops_b, _))) => ops_a != ops_b,
+        (None, None) => false,
+        _ => true,
+    });
+    match first_behind {
+        Some(i) => {
+            let ops = |side: &Side| side.segments.get(i).map(|(ops, _)| ops);
+            let behind = match (ops(a), ops(b)) {
+                (Some(ops_a), Some(ops_b)) if ops_a > ops_b => a.name,
+                (Some(_), Some(_)) => b.name,
+                // The side which never reached the target
+                (None, _) => a.name,
+                (_, None) => b.name,
+            };
+            println!(
+                "{} falls behind first at target {} (ops: {:?} vs {:?})",
+                behind,
+                i,
+                ops(a),
+                ops(b)
+            );
+        }
+        None => println!("Target progress is the same"),
+    }
+
+    println!("target,ops_a,ops_b,copies_a,copies_b");
+    let fmt = |v: Option<usize>| v.map_or(String::new(), |v| v.to_string());
+    for i in 0..input.t_sz() {
+        let (sa, sb) = (a.segments.get(i), b.segments.get(i));
+        if sa.is_none() && sb.is_none() {
+            break;
+        }
+        println!(
+            "{},{},{},{},{}",
+            i,
+            fmt(sa.map(|s| s.0)),
+            fmt(sb.map(|s| s.0)),
+            fmt(sa.map(|s| s.1)),
+            fmt(sb.map(|s| s.1))
+        );
+    }
+
+    if let Some(path) = cli.html {
+        let mut targets = (0..input.t_sz())
+            .step_by(cli.stride.max(1))
+            .collect::<Vec<_>>();
+        targets.extend(first_behind);
+        targets.sort();
+        targets.dedup();
+
+        let mut html = String::from("<html><body>\n");
+        for &i in targets.iter() {
+            html += &format!("<h2>Target {}</h2>\n<div style=\"display: flex\">\n", i);
+            for side in sides.iter() {
+                let frame = side
+                    .segments
+                    .turns
+                    .get(i)
+                    .and_then(|&turn| side.timeline.get(turn));
+                html += &format!("<div><h3>{}</h3>\n", side.name);
+                match frame {
+                    Some(vis_data) => html += &draw_svg(&vis_data, &input, cli.k).to_string(),
+                    None => html += "<p>not reached</p>",
+                }
+                html += "</div>\n";
+            }
+            html += "</div>\n";
+        }
+        html += "</body></html>\n";
+        std::fs::write(&path, html).unwrap();
+    }
+}
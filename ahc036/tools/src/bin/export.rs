@@ -0,0 +1,26 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{export_input, parse_input, GraphFormat};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to input file
+    input: PathBuf,
+    /// Output format: dot, graphml or json
+    #[clap(short, long, default_value = "json")]
+    format: GraphFormat,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let input_s = std::fs::read_to_string(&cli.input).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.input.display());
+        std::process::exit(1)
+    });
+    let input = parse_input(&input_s).unwrap_or_else(|err| {
+        eprintln!("{:#}", err);
+        std::process::exit(1)
+    });
+
+    print!("{}", export_input(&input, cli.format));
+}
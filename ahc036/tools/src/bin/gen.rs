@@ -14,12 +14,31 @@ struct Cli {
     #[clap(short, long)]
     /// Print input details in csv format
     verbose: bool,
+    /// Fix N to the specified value
+    #[clap(value_name = "N", long = "N")]
+    n: Option<usize>,
+    /// Fix T to the specified value
+    #[clap(value_name = "T", long = "T")]
+    t_sz: Option<usize>,
     /// Fix LA to the specified value
     #[clap(value_name = "LA", long = "LA")]
     al: Option<usize>,
     /// Fix LB to the specified value
     #[clap(value_name = "LB", long = "LB")]
     bl: Option<usize>,
+    /// Fix the minimum distance between vertices to the specified value
+    #[clap(long)]
+    vertex_min_distance: Option<i64>,
+    /// Fix the maximum length of edges to the specified value
+    #[clap(long)]
+    edge_max_distance: Option<i64>,
+    /// Fix the probability of erasing each removable edge to the specified value
+    #[clap(long)]
+    edge_erasing_ratio: Option<f64>,
+    /// Generate a hard case: min-la, max-la, min-lb, max-lb, tree, dense or far-targets.
+    /// May be repeated, and values fixed by the options above take precedence.
+    #[clap(long)]
+    mode: Vec<GenMode>,
 }
 
 fn main() {
@@ -32,32 +51,56 @@ fn main() {
         std::process::exit(1)
     });
     let f = std::io::BufReader::new(f);
+    let mut params = GenParams {
+        n: cli.n,
+        t_sz: cli.t_sz,
+        al: cli.al,
+        bl: cli.bl,
+        vertex_min_distance: cli.vertex_min_distance,
+        edge_max_distance: cli.edge_max_distance,
+        edge_erasing_ratio: cli.edge_erasing_ratio,
+        far_targets: false,
+    };
+    for mode in cli.mode.iter() {
+        mode.apply(&mut params);
+    }
     let mut id = 0;
     if cli.verbose {
-        println!("file,seed");
+        println!("file,seed,N,M,T,LA,LB,vertex_min_distance,edge_max_distance,edge_erasing_ratio");
     }
     for line in f.lines() {
         let line = line.unwrap();
         let line = line.trim();
-        if line.len() == 0 {
+        if line.is_empty() {
             continue;
         }
         let seed = line.parse::<u64>().unwrap_or_else(|_| {
             eprintln!("parse failed: {}", line);
             std::process::exit(1)
         });
-        let input = gen(seed, cli.al, cli.bl);
-        if cli.verbose {
-            println!("{:04},{}", id, seed);
-        }
 
-        let input = match input {
-            Ok(input) => input,
+        let (input, info) = match gen_with(seed, &params) {
+            Ok(res) => res,
             Err(err) => {
                 eprintln!("{:#}", err);
                 return;
             }
         };
+        if cli.verbose {
+            println!(
+                "{:04},{},{},{},{},{},{},{},{},{}",
+                id,
+                seed,
+                info.n,
+                info.m,
+                info.t_sz,
+                info.al,
+                info.bl,
+                info.vertex_min_distance,
+                info.edge_max_distance,
+                info.edge_erasing_ratio
+            );
+        }
 
         let mut w = std::io::BufWriter::new(
             std::fs::File::create(cli.dir.join(format!("{:04}.txt", id))).unwrap(),
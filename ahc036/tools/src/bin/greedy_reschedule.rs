@@ -0,0 +1,50 @@
+use tools::{compute_score, copy_lower_bound, greedy_reschedule, parse_input, parse_output};
+
+fn main() {
+    if std::env::args().len() != 3 {
+        eprintln!(
+            "Usage: {} <input> <output>",
+            std::env::args().next().unwrap()
+        );
+        return;
+    }
+    let in_file = std::env::args().nth(1).unwrap();
+    let out_file = std::env::args().nth(2).unwrap();
+
+    let input_s = std::fs::read_to_string(&in_file).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", in_file);
+        std::process::exit(1)
+    });
+    let output_s = std::fs::read_to_string(&out_file).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", out_file);
+        std::process::exit(1)
+    });
+
+    let input = parse_input(&input_s).unwrap_or_else(|err| {
+        eprintln!("{:#}", err);
+        std::process::exit(1)
+    });
+    let old_score = compute_score(&input, &output_s).unwrap_or_else(|err| {
+        eprintln!("{:#}", err);
+        std::process::exit(1)
+    });
+    let output = parse_output(&output_s, &input).unwrap();
+
+    let rescheduled = greedy_reschedule(&input, &output).unwrap_or_else(|err| {
+        eprintln!("{:#}", err);
+        std::process::exit(1)
+    });
+    let rescheduled_s = rescheduled.to_string();
+    let new_score = compute_score(&input, &rescheduled_s).unwrap_or_else(|err| {
+        eprintln!("rescheduled output is rejected: {:#}", err);
+        std::process::exit(1)
+    });
+
+    print!("{}", rescheduled_s);
+    eprintln!("Old score = {}", old_score);
+    eprintln!("New score = {}", new_score);
+    // A bound over every output for the input, whatever its A and route
+    let bound = copy_lower_bound(&input);
+    eprintln!("Lower bound = {}", bound);
+    eprintln!("Gap = {}", new_score.saturating_sub(bound));
+}
@@ -0,0 +1,27 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{input_from_json, validate_input};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to the json written by `export --format json`
+    json: PathBuf,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let json = std::fs::read_to_string(&cli.json).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.json.display());
+        std::process::exit(1)
+    });
+    let input = input_from_json(&json).unwrap_or_else(|err| {
+        eprintln!("{:#}", err);
+        std::process::exit(1)
+    });
+
+    // A hand-edited input is written even if it breaks the guarantees of the generator
+    for violation in validate_input(&input) {
+        eprintln!("warning: {}", violation);
+    }
+    print!("{}", input);
+}
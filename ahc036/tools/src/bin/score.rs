@@ -0,0 +1,45 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{compute_score, parse_input};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to output directory
+    out_dir: PathBuf,
+    /// Path to input directory
+    #[clap(short = 'd', long = "dir", default_value = "in")]
+    dir: PathBuf,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut files = std::fs::read_dir(&cli.out_dir)
+        .unwrap_or_else(|_| {
+            eprintln!("no such directory: {}", cli.out_dir.display());
+            std::process::exit(1)
+        })
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    println!("file,score,cause");
+    for file in files {
+        let name = file.to_string_lossy();
+        let in_file = cli.dir.join(&file);
+        let input_s = std::fs::read_to_string(&in_file).unwrap_or_else(|_| {
+            eprintln!("no such file: {}", in_file.display());
+            std::process::exit(1)
+        });
+        let output_s = std::fs::read_to_string(cli.out_dir.join(&file)).unwrap();
+
+        let res = parse_input(&input_s).and_then(|input| compute_score(&input, &output_s));
+        match res {
+            Ok(score) => println!("{},{},", name, score),
+            Err(err) => {
+                eprintln!("{}: {:#}", name, err);
+                println!("{},0,{}", name, err.cause());
+            }
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{compute_score, parse_input, solve};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to input directory
+    #[clap(short = 'd', long = "dir", default_value = "in")]
+    dir: PathBuf,
+    /// Path to output directory
+    #[clap(short = 'o', long = "out", default_value = "out")]
+    out_dir: PathBuf,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut files = std::fs::read_dir(&cli.dir)
+        .unwrap_or_else(|_| {
+            eprintln!("no such directory: {}", cli.dir.display());
+            std::process::exit(1)
+        })
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+    if !cli.out_dir.exists() {
+        std::fs::create_dir(&cli.out_dir).unwrap();
+    }
+
+    println!("file,score");
+    for file in files {
+        let name = file.to_string_lossy();
+        let input_s = std::fs::read_to_string(cli.dir.join(&file)).unwrap();
+        let res = parse_input(&input_s)
+            .map_err(anyhow::Error::from)
+            .and_then(|input| {
+                let output_s = solve(&input)?.to_string();
+                let score = compute_score(&input, &output_s)?;
+                Ok((output_s, score))
+            });
+        match res {
+            Ok((output_s, score)) => {
+                std::fs::write(cli.out_dir.join(&file), output_s).unwrap();
+                println!("{},{}", name, score);
+            }
+            Err(err) => eprintln!("{}: {:#}", name, err),
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{judge_with, parse_input, SignalUsage};
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to output directory
+    out_dir: PathBuf,
+    /// Path to input directory
+    #[clap(short = 'd', long = "dir", default_value = "in")]
+    dir: PathBuf,
+    /// Print one row per copy operation instead of one row per test case
+    #[clap(long)]
+    ops: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut files = std::fs::read_dir(&cli.out_dir)
+        .unwrap_or_else(|_| {
+            eprintln!("no such directory: {}", cli.out_dir.display());
+            std::process::exit(1)
+        })
+        .map(|entry| entry.unwrap().file_name())
+        .filter(|name| name.to_string_lossy().ends_with(".txt"))
+        .collect::<Vec<_>>();
+    files.sort();
+
+    if cli.ops {
+        println!("file,op_idx,len,p_a,p_b,moves,wasted_slots,turned_green,unused_green");
+    } else {
+        println!("file,copies,moves,slots_written,wasted_slots,turned_green,unused_green");
+    }
+    for file in files {
+        let name = file.to_string_lossy();
+        let in_file = cli.dir.join(&file);
+        let input_s = std::fs::read_to_string(&in_file).unwrap_or_else(|_| {
+            eprintln!("no such file: {}", in_file.display());
+            std::process::exit(1)
+        });
+        let output_s = std::fs::read_to_string(cli.out_dir.join(&file)).unwrap();
+
+        let mut usage = SignalUsage::new();
+        let res = parse_input(&input_s)
+            .and_then(|input| judge_with(&input, &output_s, 0, &mut [&mut usage]));
+        if let Err(err) = res {
+            // The report covers the operations before the error
+            eprintln!("{}: {:#}", name, err);
+        }
+
+        if cli.ops {
+            for copy in usage.copies.iter() {
+                println!(
+                    "{},{},{},{},{},{},{},{},{}",
+                    name,
+                    copy.op_idx,
+                    copy.cs.len,
+                    copy.cs.p_a,
+                    copy.cs.p_b,
+                    copy.moves(),
+                    copy.wasted_slots(),
+                    copy.turned_green.len(),
+                    copy.unused_green()
+                );
+            }
+        } else {
+            println!(
+                "{},{},{},{},{},{},{}",
+                name,
+                usage.copies.len(),
+                usage.moves(),
+                usage.slots_written(),
+                usage.wasted_slots(),
+                usage.turned_green(),
+                usage.unused_green()
+            );
+        }
+    }
+}
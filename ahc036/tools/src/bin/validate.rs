@@ -0,0 +1,39 @@
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{parse_input, validate_input};
+
+#[derive(Parser)]
+struct Cli {
+    /// Paths to input files
+    #[clap(required = true)]
+    files: Vec<PathBuf>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let mut ok = true;
+    for file in cli.files.iter() {
+        let input_s = std::fs::read_to_string(file).unwrap_or_else(|_| {
+            eprintln!("no such file: {}", file.display());
+            std::process::exit(1)
+        });
+
+        let input = match parse_input(&input_s) {
+            Ok(input) => input,
+            Err(err) => {
+                println!("{}: {:#}", file.display(), err);
+                ok = false;
+                continue;
+            }
+        };
+
+        for violation in validate_input(&input) {
+            println!("{}: {}", file.display(), violation);
+            ok = false;
+        }
+    }
+
+    if !ok {
+        std::process::exit(1);
+    }
+}
@@ -1,45 +1,254 @@
-use tools::{draw_svg, judge, Input};
+use clap::Parser;
+use std::path::PathBuf;
+use tools::{
+    draw_background, draw_frame, judge, judge_with, Graph, Heatmap, HeatmapMode, Input, Op,
+    Timeline, VisOptions,
+};
 
-fn main() {
-    if std::env::args().len() != 3 {
-        eprintln!(
-            "Usage: {} <input> <output>",
-            std::env::args().nth(0).unwrap()
-        );
-        return;
+// The number of frames embedded when the stride is not specified
+const DEFAULT_FRAMES: usize = 100;
+
+#[derive(Parser)]
+struct Cli {
+    /// Path to input file
+    input: PathBuf,
+    /// Path to output file
+    output: PathBuf,
+    /// The number of recent vertices to draw
+    #[clap(short, default_value = "10")]
+    k: usize,
+    /// Interval of turns embedded in the html [default: turns / 100]
+    #[clap(long)]
+    stride: Option<usize>,
+    /// Draw a shortest path from the current vertex to the current target
+    #[clap(long)]
+    path: bool,
+    /// Color vertices by visits, green-turns or turned-green over the whole replay
+    #[clap(long)]
+    heatmap: Option<HeatmapMode>,
+    /// Path to the html file to write
+    #[clap(short = 'o', long = "out", default_value = "vis.html")]
+    out: PathBuf,
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => res += "\\\"",
+            '\\' => res += "\\\\",
+            '\n' => res += "\\n",
+            // keep `</script>` in strings from closing the script element
+            '/' => res += "\\/",
+            c if (c as u32) < 0x20 => res += &format!("\\u{:04x}", c as u32),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+fn json_array<T: ToString>(values: impl IntoIterator<Item = T>) -> String {
+    let values = values
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
+
+fn render_html(
+    input: &Input,
+    timeline: &Timeline,
+    k: usize,
+    stride: usize,
+    options: &VisOptions,
+) -> String {
+    let mut turns = (0..timeline.len()).step_by(stride).collect::<Vec<_>>();
+    if let Some(last) = timeline.len().checked_sub(1) {
+        if turns.last() != Some(&last) {
+            turns.push(last);
+        }
+    }
+
+    let mut initial_a = "[]".to_string();
+    // The graph and A are drawn once, and each frame only carries what changes on its turn
+    let mut background = String::new();
+    let mut frames = vec![];
+    for &t in turns.iter() {
+        let vis_data = timeline.get(t).unwrap();
+        let state = vis_data.state();
+        if t == 0 {
+            initial_a = json_array(state.a());
+            background = draw_background(&vis_data, input, options).to_string();
+        }
+
+        let (op, copy, comments) = match (vis_data.commented_op(), vis_data.initial_comments()) {
+            (Some(commented_op), _) => {
+                let copy = match commented_op.op {
+                    Op::CopySignals(cs) => json_array([cs.len, cs.p_a, cs.p_b]),
+                    Op::Move(_) => "null".to_string(),
+                };
+                let op = commented_op.op.to_string();
+                (op.trim_end().to_string(), copy, &commented_op.comments[..])
+            }
+            (None, comments) => (String::new(), "null".to_string(), comments.unwrap_or(&[])),
+        };
+
+        frames.push(format!(
+            "{{\"turn\":{},\"score\":{},\"target\":{},\"cur\":{},\"op\":{},\"copy\":{},\"b\":{},\"comments\":{},\"layer\":{}}}",
+            t,
+            state.score(),
+            state.target_idx(),
+            state.cur_v(),
+            json_string(&op),
+            copy,
+            json_array(state.b()),
+            json_array(comments.iter().map(|c| json_string(c))),
+            json_string(&draw_frame(&vis_data, input, k, options))
+        ));
     }
-    let in_file = std::env::args().nth(1).unwrap();
-    let out_file = std::env::args().nth(2).unwrap();
 
-    let input_s = std::fs::read_to_string(&in_file).unwrap_or_else(|_| {
-        eprintln!("no such file: {}", in_file);
+    format!(
+        r#"<html>
+<head>
+<meta charset="utf-8">
+<style>
+body {{ font-family: sans-serif; }}
+#panels {{ margin-left: 16px; max-width: 480px; }}
+.cells span {{ display: inline-block; min-width: 2.5em; margin: 1px; text-align: center; background: #eee; font-family: monospace; }}
+.cells span.copied {{ background: #9cd3b4; }}
+.cells span.cur {{ outline: 2px solid blue; }}
+pre {{ white-space: pre-wrap; margin: 0; }}
+</style>
+</head>
+<body>
+<div>
+<button id="play">Play</button>
+<input type="range" id="slider" min="0" max="{max}" value="{max}" style="width: 600px">
+<span id="turn"></span>
+</div>
+<div style="display: flex">
+<div id="svg"></div>
+<div id="panels">
+<h3>Operation</h3><pre id="op"></pre>
+<h3>A</h3><div id="a" class="cells"></div>
+<h3>B</h3><div id="b" class="cells"></div>
+<h3>Comments</h3><pre id="comments"></pre>
+</div>
+</div>
+<script>
+const T_SZ = {t_sz};
+const A = {initial_a};
+document.getElementById("svg").innerHTML = {background};
+const frame = document.getElementById("frame");
+const FRAMES = [
+{frames}
+];
+const slider = document.getElementById("slider");
+const play = document.getElementById("play");
+let timer = null;
+
+function cells(values, lo, hi, cur) {{
+  return values.map((v, i) => {{
+    const cls = (lo <= i && i < hi ? "copied" : "") + (v === cur ? " cur" : "");
+    return `<span class="${{cls}}">${{v < 0 ? "-" : v}}</span>`;
+  }}).join("");
+}}
+
+function show(i) {{
+  const f = FRAMES[i];
+  slider.value = i;
+  document.getElementById("turn").textContent =
+    `Turn ${{f.turn}} / ${{FRAMES[FRAMES.length - 1].turn}}, Score = ${{f.score}}, Targets = ${{f.target}} / ${{T_SZ}}`;
+  frame.innerHTML = f.layer;
+  document.getElementById("op").textContent = f.op;
+  const [len, pa, pb] = f.copy || [0, 0, 0];
+  document.getElementById("a").innerHTML = cells(A, pa, pa + len, f.cur);
+  document.getElementById("b").innerHTML = cells(f.b, pb, pb + len, f.cur);
+  document.getElementById("comments").textContent = f.comments.join("\n");
+}}
+
+function stop() {{
+  clearInterval(timer);
+  timer = null;
+  play.textContent = "Play";
+}}
+
+slider.oninput = () => show(Number(slider.value));
+play.onclick = () => {{
+  if (timer !== null) {{
+    stop();
+    return;
+  }}
+  if (Number(slider.value) === FRAMES.length - 1) {{
+    show(0);
+  }}
+  play.textContent = "Pause";
+  timer = setInterval(() => {{
+    const i = Number(slider.value) + 1;
+    if (i < FRAMES.length) {{
+      show(i);
+    }} else {{
+      stop();
+    }}
+  }}, 100);
+}};
+if (FRAMES.length > 0) {{
+  show(FRAMES.length - 1);
+}}
+</script>
+</body>
+</html>
+"#,
+        max = turns.len().saturating_sub(1),
+        t_sz = input.t_sz(),
+        initial_a = initial_a,
+        background = json_string(&background),
+        frames = frames.join(",\n"),
+    )
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let input_s = std::fs::read_to_string(&cli.input).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.input.display());
         std::process::exit(1)
     });
-    let output_s = std::fs::read_to_string(&out_file).unwrap_or_else(|_| {
-        eprintln!("no such file: {}", out_file);
+    let output_s = std::fs::read_to_string(&cli.output).unwrap_or_else(|_| {
+        eprintln!("no such file: {}", cli.output.display());
         std::process::exit(1)
     });
 
-    let mut vis_data_vec = vec![];
+    let mut timeline = Timeline::new();
 
     let mut input_res = Input::new();
 
-    let k = 10;
-    let svg = match judge(&input_s, &output_s, &mut vis_data_vec, &mut input_res, k) {
+    match judge(&input_s, &output_s, &mut timeline, &mut input_res, cli.k) {
         Ok(judge_result) => {
             eprintln!("Score = {}", judge_result.score);
-
-            let vis_data = vis_data_vec.last().unwrap();
-            draw_svg(&vis_data, &input_res, k).to_string()
         }
-        Err(err) => {
-            eprintln!("{:#}", err);
+        Err(failure) => {
+            eprintln!("{:#}", failure);
+            // The frames show the states until just before the error
             eprintln!("Score = 0");
-
-            String::new()
         }
-    };
+    }
 
-    let vis = format!("<html><body>{}</body></html>", svg);
-    std::fs::write("vis.html", &vis).unwrap();
+    let stride = cli
+        .stride
+        .unwrap_or(timeline.len().div_ceil(DEFAULT_FRAMES))
+        .max(1);
+    let heatmap = cli.heatmap.map(|mode| {
+        let mut heatmap = Heatmap::new();
+        // Errors were already reported, and the measures until the error are drawn
+        let _ = judge_with(&input_res, &output_s, cli.k, &mut [&mut heatmap]);
+        (mode, heatmap.values(mode).to_vec())
+    });
+    let options = VisOptions {
+        shortest_path: cli.path.then(|| Graph::new(&input_res)),
+        heatmap,
+    };
+    let vis = render_html(&input_res, &timeline, cli.k, stride, &options);
+    std::fs::write(&cli.out, &vis).unwrap();
 }
@@ -1,30 +1,60 @@
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use rand::{seq::SliceRandom, Rng};
 use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
-use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::{fmt, str};
-use svg::node::element::SVG;
-
+use svg::node::element::{Group, SVG};
+
+mod lib_analysis;
+mod lib_bound;
+mod lib_builder;
+mod lib_directive;
+mod lib_export;
+mod lib_gen_mode;
+mod lib_graph;
+mod lib_heatmap;
+mod lib_observer;
+mod lib_reschedule;
+mod lib_solve;
+mod lib_timeline;
+mod lib_usage;
+mod lib_validate;
 mod lib_vis;
+pub use lib_analysis::{analyze_input, InputStats};
+pub use lib_bound::copy_lower_bound;
+pub use lib_builder::{parse_input_dsl, InputBuilder};
+pub use lib_directive::{parse_directive, Directive};
+pub use lib_export::{export_input, input_from_json, GraphFormat};
+pub use lib_gen_mode::GenMode;
+pub use lib_graph::Graph;
+pub use lib_heatmap::{Heatmap, HeatmapMode};
+pub use lib_observer::{Observer, Scorer};
+pub use lib_reschedule::{copies_needed, greedy_reschedule};
+pub use lib_solve::solve;
+pub use lib_timeline::{Progress, Timeline};
+pub use lib_usage::{CopyUsage, SignalUsage};
+pub use lib_validate::{validate_input, InputViolation};
 use lib_vis::*;
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
-#[cfg(target_arch = "wasm32")]
-use once_cell::sync::Lazy;
-
-#[cfg(target_arch = "wasm32")]
-use std::sync::Mutex;
-
 const N: usize = 600;
 const T_SZ: usize = 600;
-const AL_LB: usize = N;
-const AL_UB: usize = N * 2;
 const BL_LB_SQRT: usize = 2;
 const BL_UB_SQRT: usize = 5;
 const COORD_MIN: i64 = 0;
 const COORD_MAX: i64 = 1000;
+const VERTEX_MIN_DISTANCE_LB: i64 = 20;
+const VERTEX_MIN_DISTANCE_UB: i64 = 30;
+const EDGE_MAX_DISTANCE_LB: i64 = 80;
+const EDGE_MAX_DISTANCE_UB: i64 = 140;
+const EDGE_ERASING_RATIO_LB: f64 = 0.0;
+const EDGE_ERASING_RATIO_UB: f64 = 0.5;
+// Give up when construct_planar_graph fails this many times in a row
+const MAX_CONSTRUCTION_CNT: usize = 100;
+// Give up when this many points per vertex were rejected by the minimum distance
+const MAX_COORD_TRIAL_PER_VERTEX: usize = 1000;
 const MAX_OPERATION_CNT: usize = 100000;
 
 fn read<T: Copy + PartialOrd + std::fmt::Display + std::str::FromStr>(
@@ -59,6 +89,12 @@ pub struct Input {
     coordinates: Vec<(i64, i64)>,
 }
 
+impl Default for Input {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Input {
     pub fn new() -> Self {
         Input {
@@ -72,6 +108,38 @@ impl Input {
             coordinates: vec![],
         }
     }
+
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    pub fn m(&self) -> usize {
+        self.m
+    }
+
+    pub fn t_sz(&self) -> usize {
+        self.t_sz
+    }
+
+    pub fn al(&self) -> usize {
+        self.al
+    }
+
+    pub fn bl(&self) -> usize {
+        self.bl
+    }
+
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    pub fn t(&self) -> &[usize] {
+        &self.t
+    }
+
+    pub fn coordinates(&self) -> &[(i64, i64)] {
+        &self.coordinates
+    }
 }
 
 impl fmt::Display for Input {
@@ -106,13 +174,52 @@ impl fmt::Display for Input {
     }
 }
 
-fn parse_input(s: &str) -> Result<Input> {
-    let mut tokens = s.split_whitespace();
+// Whitespace-separated tokens which remember the line of the last token read
+struct Tokens<'a> {
+    lines: std::iter::Enumerate<str::Lines<'a>>,
+    cur: str::SplitWhitespace<'a>,
+    line: usize,
+}
 
+impl<'a> Tokens<'a> {
+    fn new(s: &'a str) -> Self {
+        Tokens {
+            lines: s.lines().enumerate(),
+            cur: "".split_whitespace(),
+            line: 1,
+        }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        loop {
+            if let Some(token) = self.cur.next() {
+                return Some(token);
+            }
+            let (i, line) = self.lines.next()?;
+            self.line = i + 1;
+            self.cur = line.split_whitespace();
+        }
+    }
+}
+
+pub fn parse_input(s: &str) -> Result<Input, JudgeError> {
+    let mut tokens = Tokens::new(s);
+    parse_input_tokens(&mut tokens).map_err(|err| JudgeError::Parse {
+        target: ParseTarget::Input,
+        line: tokens.line,
+        message: format!("{:#}", err),
+    })
+}
+
+fn parse_input_tokens(tokens: &mut Tokens) -> Result<Input> {
     // You can use values that do not satisfy the constraints of the problem statement as input.
     // We do not guarantee the behavior when such values are used.
-    let n: usize = read(tokens.next(), 0, usize::MAX).context("N")?;
-    let m: usize = read(tokens.next(), n - 1, n * (n - 1)).context("M")?;
+    let n: usize = read(tokens.next(), 1, usize::MAX).context("N")?;
+    let m: usize = read(tokens.next(), n - 1, n.saturating_mul(n - 1)).context("M")?;
     let t_sz: usize = read(tokens.next(), 0, usize::MAX).context("T")?;
     let al: usize = read(tokens.next(), 0, usize::MAX).context("L_A")?;
     let bl = read(tokens.next(), 0, usize::MAX).context("L_B")?;
@@ -149,7 +256,7 @@ fn parse_input(s: &str) -> Result<Input> {
     })
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(target_arch = "wasm32", wasm_bindgen(getter_with_clone))]
 pub struct CopySignals {
     pub len: usize,
@@ -157,8 +264,8 @@ pub struct CopySignals {
     pub p_b: usize,
 }
 
-#[derive(Clone, Copy)]
-enum Op {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
     CopySignals(CopySignals),
     Move(usize),
 }
@@ -174,67 +281,242 @@ impl fmt::Display for Op {
     }
 }
 
-#[allow(dead_code)]
-#[derive(Clone)]
-struct CommentedOp {
-    op: Op,
-    comments: Vec<String>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommentedOp {
+    pub op: Op,
+    /// Comments written just before the operation
+    pub comments: Vec<String>,
 }
 
-#[derive(Clone)]
-struct Output {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Output {
     initial_comments: Vec<String>,
     initial_a: Vec<usize>,
     commented_ops: Vec<CommentedOp>,
+    // comments after the last operation
+    trailing_comments: Vec<String>,
+    // line number of each operation in the output text
+    op_lines: Vec<usize>,
 }
 
-fn parse_output(s: &str, input: &Input) -> Result<Output> {
+impl Output {
+    /// Comments written before the array A
+    pub fn initial_comments(&self) -> &[String] {
+        &self.initial_comments
+    }
+
+    pub fn initial_a(&self) -> &[usize] {
+        &self.initial_a
+    }
+
+    pub fn commented_ops(&self) -> &[CommentedOp] {
+        &self.commented_ops
+    }
+
+    pub fn trailing_comments(&self) -> &[String] {
+        &self.trailing_comments
+    }
+}
+
+fn write_comments(f: &mut fmt::Formatter, comments: &[String]) -> fmt::Result {
+    for comment in comments.iter() {
+        if comment.is_empty() {
+            writeln!(f, "#")?;
+        } else {
+            writeln!(f, "# {}", comment)?;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_comments(f, &self.initial_comments)?;
+
+        for (i, a) in self.initial_a.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", a)?;
+        }
+        writeln!(f)?;
+
+        for commented_op in self.commented_ops.iter() {
+            write_comments(f, &commented_op.comments)?;
+            write!(f, "{}", commented_op.op)?;
+        }
+
+        write_comments(f, &self.trailing_comments)
+    }
+}
+
+/// Builds an `Output` whose `Display` is read back to the same value by `parse_output`.
+///
+/// Comments are trimmed and split into lines in the same way as `parse_output` reads them.
+#[derive(Clone, Debug, Default)]
+pub struct OutputBuilder {
+    initial_comments: Vec<String>,
+    initial_a: Vec<usize>,
+    commented_ops: Vec<CommentedOp>,
+    comments: Vec<String>,
+}
+
+impl OutputBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_a(&mut self, a: Vec<usize>) -> &mut Self {
+        self.initial_a = a;
+        self
+    }
+
+    /// Add a comment written before the array A
+    pub fn initial_comment(&mut self, comment: &str) -> &mut Self {
+        self.initial_comments.extend(comment_lines(comment));
+        self
+    }
+
+    /// Add a comment attached to the next operation
+    pub fn comment(&mut self, comment: &str) -> &mut Self {
+        self.comments.extend(comment_lines(comment));
+        self
+    }
+
+    pub fn push(&mut self, op: Op) -> &mut Self {
+        self.commented_ops.push(CommentedOp {
+            op,
+            comments: std::mem::take(&mut self.comments),
+        });
+        self
+    }
+
+    pub fn copy_signals(&mut self, len: usize, p_a: usize, p_b: usize) -> &mut Self {
+        self.push(Op::CopySignals(CopySignals { len, p_a, p_b }))
+    }
+
+    pub fn move_to(&mut self, v: usize) -> &mut Self {
+        self.push(Op::Move(v))
+    }
+
+    /// Comments added after the last operation become trailing comments.
+    pub fn build(&self) -> Output {
+        // Lines are numbered in the same way as the text written by `Display`
+        let mut line = self.initial_comments.len() + 1;
+        let mut op_lines = vec![];
+        for commented_op in self.commented_ops.iter() {
+            line += commented_op.comments.len() + 1;
+            op_lines.push(line);
+        }
+
+        Output {
+            initial_comments: self.initial_comments.clone(),
+            initial_a: self.initial_a.clone(),
+            commented_ops: self.commented_ops.clone(),
+            trailing_comments: self.comments.clone(),
+            op_lines,
+        }
+    }
+}
+
+fn comment_lines(comment: &str) -> impl Iterator<Item = String> + '_ {
+    comment.split('\n').map(|line| line.trim().to_string())
+}
+
+pub fn parse_output(s: &str, input: &Input) -> Result<Output, JudgeError> {
     let mut initial_comments = vec![];
     let mut initial_a = vec![];
     let mut commented_ops = vec![];
+    let mut op_lines = vec![];
     let mut comments = vec![];
 
     let mut initialized_a = false;
-    for (i, line) in s.trim().lines().enumerate() {
+    for (i, line) in s.lines().enumerate() {
         let line = line.trim();
+        let parse_error = |err: anyhow::Error| JudgeError::Parse {
+            target: ParseTarget::Output,
+            line: i + 1,
+            message: format!("{:#}", err),
+        };
 
         if !line.is_empty() {
             if line.starts_with("#") {
                 let comment = line.strip_prefix("#").unwrap().trim().to_string();
                 comments.push(comment);
             } else if !initialized_a {
-                // Set values of A
-                let mut tokens = line.split_whitespace();
-                for _ in 0..input.al {
-                    let val: usize = read(tokens.next(), 0, input.n - 1)
-                        .context(format!("{}-th line", i + 1))?;
-                    initial_a.push(val)
-                }
+                initial_a = parse_a(line, input).map_err(parse_error)?;
                 initial_comments = comments.clone();
                 comments.clear();
                 initialized_a = true;
-                ensure!(
-                    tokens.next() == None,
-                    format!("{}-th line must have {} elements.", i, input.al)
-                );
             } else {
-                let op = parse_op(line, input).context(format!("{}-th line", i + 1))?;
+                let op = parse_op(line, input).map_err(parse_error)?;
                 commented_ops.push(CommentedOp {
                     op,
                     comments: comments.clone(),
                 });
+                op_lines.push(i + 1);
                 comments.clear();
             }
         }
     }
 
+    if !initialized_a {
+        initial_comments = comments;
+        comments = vec![];
+    }
+
     Ok(Output {
         initial_comments,
         initial_a,
         commented_ops,
+        trailing_comments: comments,
+        op_lines,
     })
 }
 
+// An operation and its line number
+type LinedOp = (Op, usize);
+
+// The array A and the operations, without the comments.
+// Fails in the same way as `parse_output`.
+fn parse_ops(s: &str, input: &Input) -> Result<(Vec<usize>, Vec<LinedOp>), JudgeError> {
+    let mut initial_a = None;
+    let mut ops = vec![];
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parse_error = |err: anyhow::Error| JudgeError::Parse {
+            target: ParseTarget::Output,
+            line: i + 1,
+            message: format!("{:#}", err),
+        };
+
+        if initial_a.is_none() {
+            initial_a = Some(parse_a(line, input).map_err(parse_error)?);
+        } else {
+            ops.push((parse_op(line, input).map_err(parse_error)?, i + 1));
+        }
+    }
+    Ok((initial_a.unwrap_or_default(), ops))
+}
+
+fn parse_a(line: &str, input: &Input) -> Result<Vec<usize>> {
+    let mut tokens = line.split_whitespace();
+    let mut a = vec![];
+    for j in 0..input.al {
+        let val: usize = read(tokens.next(), 0, input.n - 1).context(format!("A_{}", j))?;
+        a.push(val)
+    }
+    ensure!(
+        tokens.next().is_none(),
+        "The line must have {} elements.",
+        input.al
+    );
+    Ok(a)
+}
+
 fn parse_op(line: &str, input: &Input) -> Result<Op> {
     let mut tokens = line.split_whitespace();
     let type_: char = read(tokens.next(), '\0', char::MAX).context("operation type")?;
@@ -245,7 +527,7 @@ fn parse_op(line: &str, input: &Input) -> Result<Op> {
             let p_a = read(tokens.next(), 0, input.al).context("P_A")?;
             let p_b = read(tokens.next(), 0, input.bl).context("P_B")?;
             ensure!(
-                tokens.next() == None,
+                tokens.next().is_none(),
                 "Operation s must be like 's, l, P_A, P_B'"
             );
 
@@ -253,7 +535,7 @@ fn parse_op(line: &str, input: &Input) -> Result<Op> {
         }
         'm' => {
             let v: usize = read(tokens.next(), 0, input.n - 1).context("v")?;
-            ensure!(tokens.next() == None, "Operation m must be like 'm v'");
+            ensure!(tokens.next().is_none(), "Operation m must be like 'm v'");
             Ok(Op::Move(v))
         }
         _ => Err(anyhow!("Invalid value: {}", type_).context("operation type"))?,
@@ -261,14 +543,16 @@ fn parse_op(line: &str, input: &Input) -> Result<Op> {
 }
 
 #[derive(Clone)]
-struct State {
+pub struct State {
     score: usize,
     op_cnt: usize,
     target_idx: usize,
     cur_v: usize,
     a: Vec<usize>,
     b: Vec<isize>,
-    visited: Vec<usize>,
+    // the latest k vertices that have been visited
+    visited: VecDeque<usize>,
+    k: usize,
 
     traffic_light: Vec<usize>,
     // red: 0,
@@ -276,32 +560,74 @@ struct State {
 }
 
 impl State {
-    fn new(input: &Input, output: &Output) -> State {
+    fn new(input: &Input, initial_a: &[usize], k: usize) -> State {
         State {
             score: 0,
             op_cnt: 0,
             target_idx: 0,
             cur_v: 0,
-            a: output.initial_a.clone(),
+            a: initial_a.to_vec(),
             b: vec![-1; input.bl],
-            visited: vec![0],
+            visited: VecDeque::from([0]),
+            k,
             traffic_light: vec![0; input.n],
         }
     }
 
-    fn copy_signals(&mut self, input: &Input, cs: CopySignals) -> Result<()> {
-        self.op_cnt += 1;
-        self.score += 1;
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn op_cnt(&self) -> usize {
+        self.op_cnt
+    }
+
+    pub fn target_idx(&self) -> usize {
+        self.target_idx
+    }
+
+    pub fn cur_v(&self) -> usize {
+        self.cur_v
+    }
+
+    pub fn a(&self) -> &[usize] {
+        &self.a
+    }
+
+    /// -1 means that the slot has never been written
+    pub fn b(&self) -> &[isize] {
+        &self.b
+    }
+
+    pub fn visited(&self) -> &VecDeque<usize> {
+        &self.visited
+    }
+
+    /// The number of slots of B holding each vertex. A vertex is green if it is positive.
+    pub fn traffic_light(&self) -> &[usize] {
+        &self.traffic_light
+    }
 
+    // A rejected operation leaves the state unchanged.
+    fn copy_signals(&mut self, input: &Input, cs: CopySignals) -> Result<(), Violation> {
         // Copy from array A
         if cs.p_a + cs.len > input.al {
-            bail!("R_A must be included in the array A.");
+            return Err(Violation::RangeA {
+                p_a: cs.p_a,
+                len: cs.len,
+            });
         }
 
         // Paste to array B
         if cs.p_b + cs.len > input.bl {
-            bail!("R_B must be included in the array B.");
+            return Err(Violation::RangeB {
+                p_b: cs.p_b,
+                len: cs.len,
+            });
         }
+
+        self.op_cnt += 1;
+        self.score += 1;
         for i in 0..cs.len {
             if self.b[i + cs.p_b] >= 0 {
                 self.traffic_light[self.b[i + cs.p_b] as usize] -= 1;
@@ -316,26 +642,29 @@ impl State {
         Ok(())
     }
 
-    fn move_(&mut self, input: &Input, g: &Vec<HashSet<usize>>, v: usize, k: usize) -> Result<()> {
-        self.op_cnt += 1;
+    fn move_(&mut self, input: &Input, g: &Graph, v: usize) -> Result<(), Violation> {
         if self.traffic_light[v] == 0 {
-            bail!(format!("Vertex {} must be green.", v));
+            return Err(Violation::RedVertex {
+                from: self.cur_v,
+                to: v,
+            });
         }
 
-        if g[self.cur_v].contains(&v) {
+        if g.is_adjacent(self.cur_v, v) {
+            self.op_cnt += 1;
             if self.target_idx < input.t_sz && input.t[self.target_idx] == v {
                 self.target_idx += 1;
             }
             self.cur_v = v;
-            self.visited.push(v);
-            if self.visited.len() > k {
-                self.visited.remove(0);
+            self.visited.push_back(v);
+            if self.visited.len() > self.k {
+                self.visited.pop_front();
             }
         } else {
-            bail!(format!(
-                "You tried to move to vetex {} which is not adjacent to vertex {}.",
-                v, self.cur_v
-            ));
+            return Err(Violation::NotAdjacent {
+                from: self.cur_v,
+                to: v,
+            });
         }
 
         Ok(())
@@ -350,6 +679,186 @@ pub struct VisData {
     commented_op: Option<CommentedOp>,
 }
 
+impl VisData {
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    /// Comments before A, only given in the initial turn
+    pub fn initial_comments(&self) -> Option<&[String]> {
+        self.initial_comments.as_deref()
+    }
+
+    /// The operation applied in this turn, `None` in the initial turn
+    pub fn commented_op(&self) -> Option<&CommentedOp> {
+        self.commented_op.as_ref()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseTarget {
+    Input,
+    Output,
+}
+
+impl fmt::Display for ParseTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseTarget::Input => write!(f, "input"),
+            ParseTarget::Output => write!(f, "output"),
+        }
+    }
+}
+
+/// A rule broken by a single operation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// R_A = [p_a, p_a + len) is not included in the array A
+    RangeA { p_a: usize, len: usize },
+    /// R_B = [p_b, p_b + len) is not included in the array B
+    RangeB { p_b: usize, len: usize },
+    /// Tried to move from `from` to the red vertex `to`
+    RedVertex { from: usize, to: usize },
+    /// Tried to move from `from` to `to`, which are not adjacent
+    NotAdjacent { from: usize, to: usize },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Violation::RangeA { .. } => write!(f, "R_A must be included in the array A."),
+            Violation::RangeB { .. } => write!(f, "R_B must be included in the array B."),
+            Violation::RedVertex { to, .. } => write!(f, "Vertex {} must be green.", to),
+            Violation::NotAdjacent { from, to } => write!(
+                f,
+                "You tried to move to vetex {} which is not adjacent to vertex {}.",
+                to, from
+            ),
+        }
+    }
+}
+
+/// The reason why `judge` rejected a pair of input and output.
+/// `op_idx` is 0-indexed and `line` is 1-indexed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JudgeError {
+    Parse {
+        target: ParseTarget,
+        line: usize,
+        message: String,
+    },
+    Op {
+        op_idx: usize,
+        line: usize,
+        violation: Violation,
+    },
+    TooManyOps {
+        op_idx: usize,
+        line: usize,
+    },
+    TargetsNotVisited {
+        target_idx: usize,
+        t_sz: usize,
+    },
+}
+
+impl JudgeError {
+    /// Short name of the broken rule, suitable for grouping failures
+    pub fn cause(&self) -> &'static str {
+        match self {
+            JudgeError::Parse { .. } => "parse",
+            JudgeError::Op { violation, .. } => match violation {
+                Violation::RangeA { .. } => "range_a",
+                Violation::RangeB { .. } => "range_b",
+                Violation::RedVertex { .. } => "red_vertex",
+                Violation::NotAdjacent { .. } => "not_adjacent",
+            },
+            JudgeError::TooManyOps { .. } => "too_many_ops",
+            JudgeError::TargetsNotVisited { .. } => "targets_not_visited",
+        }
+    }
+
+    /// Index of the failing operation
+    pub fn op_idx(&self) -> Option<usize> {
+        match *self {
+            JudgeError::Op { op_idx, .. } | JudgeError::TooManyOps { op_idx, .. } => Some(op_idx),
+            _ => None,
+        }
+    }
+
+    /// Line of the input or the output which caused the error
+    pub fn line(&self) -> Option<usize> {
+        match *self {
+            JudgeError::Parse { line, .. }
+            | JudgeError::Op { line, .. }
+            | JudgeError::TooManyOps { line, .. } => Some(line),
+            JudgeError::TargetsNotVisited { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for JudgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JudgeError::Parse {
+                target,
+                line,
+                message,
+            } => write!(f, "{}: {}-th line: {}", target, line, message),
+            JudgeError::Op {
+                op_idx,
+                line,
+                violation,
+            } => write!(
+                f,
+                "output: {}-th operation ({}-th line): {}",
+                op_idx, line, violation
+            ),
+            JudgeError::TooManyOps { .. } => write!(
+                f,
+                "output: You can perform operations up to {} times",
+                MAX_OPERATION_CNT
+            ),
+            JudgeError::TargetsNotVisited { target_idx, t_sz } => write!(
+                f,
+                "You must visit all targets. ({} / {} visited)",
+                target_idx, t_sz
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JudgeError {}
+
+/// A rejection by `judge` together with how far the replay got
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JudgeFailure {
+    pub error: JudgeError,
+    /// `None` if the input or the output could not be parsed
+    pub progress: Option<Progress>,
+}
+
+impl From<JudgeError> for JudgeFailure {
+    fn from(error: JudgeError) -> Self {
+        JudgeFailure {
+            error,
+            progress: None,
+        }
+    }
+}
+
+impl fmt::Display for JudgeFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        if let Some(progress) = &self.progress {
+            write!(f, "\n{}", progress)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for JudgeFailure {}
+
 pub struct JudgeResult {
     pub score: usize,
 }
@@ -357,64 +866,193 @@ pub struct JudgeResult {
 pub fn judge(
     input_s: &str,
     output_s: &str,
-    vis_data_vec: &mut Vec<VisData>,
+    timeline: &mut Timeline,
     input_res: &mut Input,
     k: usize,
-) -> Result<JudgeResult> {
-    let input = parse_input(input_s).context("input")?;
+) -> Result<JudgeResult, JudgeFailure> {
+    let input = parse_input(input_s)?;
     *input_res = input.clone();
-    let output = parse_output(output_s, &input).context("output")?;
+    *timeline = Timeline::new();
+    judge_with(&input, output_s, k, &mut [timeline]).map_err(|error| JudgeFailure {
+        error,
+        progress: timeline.progress(),
+    })
+}
+
+/// Replay `output_s` on `input` while notifying each of `observers`
+pub fn judge_with(
+    input: &Input,
+    output_s: &str,
+    k: usize,
+    observers: &mut [&mut dyn Observer],
+) -> Result<JudgeResult, JudgeError> {
+    let output = parse_output(output_s, input)?;
+    let ops = output
+        .commented_ops
+        .iter()
+        .zip(&output.op_lines)
+        .map(|(commented_op, &line)| (commented_op.op, line, &commented_op.comments[..]));
+    run(
+        input,
+        &output.initial_a,
+        &output.initial_comments,
+        ops,
+        k,
+        observers,
+    )
+}
+
+// An operation to replay, with its line number and its comments
+type ReplayOp<'a> = (Op, usize, &'a [String]);
+
+fn run<'a>(
+    input: &Input,
+    initial_a: &[usize],
+    initial_comments: &[String],
+    ops: impl Iterator<Item = ReplayOp<'a>>,
+    k: usize,
+    observers: &mut [&mut dyn Observer],
+) -> Result<JudgeResult, JudgeError> {
+    let g = Graph::new(input);
+
+    let mut scorer = Scorer::default();
+    let mut all_observers: Vec<&mut dyn Observer> = vec![];
+    for observer in observers.iter_mut() {
+        all_observers.push(*observer);
+    }
+    all_observers.push(&mut scorer);
+    let observers = &mut all_observers[..];
 
-    let mut g = vec![HashSet::new(); input.n];
-    for &(u, v) in input.edges.iter() {
-        g[u].insert(v);
-        g[v].insert(u);
+    let mut state = State::new(input, initial_a, k);
+    for observer in observers.iter_mut() {
+        observer.on_init(input, &state, initial_comments);
     }
 
-    let mut state = State::new(&input, &output);
-    vis_data_vec.push(VisData {
-        state: state.clone(),
-        initial_comments: Some(output.initial_comments.clone()),
-        commented_op: None,
-    });
+    let res = replay(input, ops, &g, &mut state, observers);
+    if let Err(err) = &res {
+        for observer in observers.iter_mut() {
+            observer.on_error(input, &state, err);
+        }
+    }
+    drop(all_observers);
+    res.map(|()| JudgeResult {
+        score: scorer.score,
+    })
+}
 
-    for (i, commented_op) in output.clone().commented_ops.into_iter().enumerate() {
+fn replay<'a>(
+    input: &Input,
+    ops: impl Iterator<Item = ReplayOp<'a>>,
+    g: &Graph,
+    state: &mut State,
+    observers: &mut [&mut dyn Observer],
+) -> Result<(), JudgeError> {
+    for (i, (op, line, comments)) in ops.enumerate() {
         if i >= MAX_OPERATION_CNT {
-            bail!("You can perform operations up to 100000 times");
+            return Err(JudgeError::TooManyOps { op_idx: i, line });
         }
-        let res = match commented_op.op {
-            Op::CopySignals(cs) => state.copy_signals(&input, cs),
-            Op::Move(v) => state.move_(&input, &g, v, k),
+        let op_error = |violation| JudgeError::Op {
+            op_idx: i,
+            line,
+            violation,
         };
-        res.context(format!("{}-th operation", i))?;
 
-        vis_data_vec.push(VisData {
-            state: state.clone(),
-            initial_comments: None,
-            commented_op: Some(commented_op),
-        });
+        match op {
+            Op::CopySignals(cs) => {
+                state.copy_signals(input, cs).map_err(op_error)?;
+                for observer in observers.iter_mut() {
+                    observer.on_copy_signals(input, state, i, cs, comments);
+                }
+            }
+            Op::Move(v) => {
+                let target_idx = state.target_idx;
+                state.move_(input, g, v).map_err(op_error)?;
+                for observer in observers.iter_mut() {
+                    observer.on_move(input, state, i, v, comments);
+                }
+                if state.target_idx != target_idx {
+                    for observer in observers.iter_mut() {
+                        observer.on_target_reached(input, state, target_idx);
+                    }
+                }
+            }
+        }
     }
 
     if state.target_idx != input.t_sz {
-        bail!("You must visit all targets.");
+        return Err(JudgeError::TargetsNotVisited {
+            target_idx: state.target_idx,
+            t_sz: input.t_sz,
+        });
     }
-    Ok(JudgeResult { score: state.score })
+    Ok(())
+}
+
+/// Score `output_s` without recording anything.
+/// Equivalent to `judge` for the score and the error, but much faster on long outputs.
+pub fn compute_score(input: &Input, output_s: &str) -> Result<usize, JudgeError> {
+    let (initial_a, ops) = parse_ops(output_s, input)?;
+    let ops = ops.iter().map(|&(op, line)| (op, line, &[][..]));
+    run(input, &initial_a, &[], ops, 0, &mut []).map(|res| res.score)
+}
+
+/// Values fixed instead of being drawn by `gen_with`
+#[derive(Clone, Debug, Default)]
+pub struct GenParams {
+    pub n: Option<usize>,
+    pub t_sz: Option<usize>,
+    pub al: Option<usize>,
+    pub bl: Option<usize>,
+    pub vertex_min_distance: Option<i64>,
+    pub edge_max_distance: Option<i64>,
+    pub edge_erasing_ratio: Option<f64>,
+    /// Draw each target among the vertices far from the previous one
+    pub far_targets: bool,
+}
+
+/// Parameters used to generate an input, whether fixed or drawn
+#[derive(Clone, Debug)]
+pub struct GenInfo {
+    pub n: usize,
+    pub m: usize,
+    pub t_sz: usize,
+    pub al: usize,
+    pub bl: usize,
+    pub vertex_min_distance: i64,
+    pub edge_max_distance: i64,
+    pub edge_erasing_ratio: f64,
 }
 
 pub fn gen(seed: u64, al: Option<usize>, bl: Option<usize>) -> Result<Input> {
+    let params = GenParams {
+        al,
+        bl,
+        ..Default::default()
+    };
+    gen_with(seed, &params).map(|(input, _)| input)
+}
+
+pub fn gen_with(seed: u64, params: &GenParams) -> Result<(Input, GenInfo)> {
     let mut rng = ChaCha20Rng::seed_from_u64(seed ^ 94);
 
-    let al = if let Some(val) = al {
+    let n = params.n.unwrap_or(N);
+    if n < 2 {
+        Err(anyhow!("Out of range: {}", n).context("N"))?;
+    }
+    let t_sz = params.t_sz.unwrap_or(T_SZ);
+    let (al_lb, al_ub) = (n, n * 2);
+
+    let al = if let Some(val) = params.al {
         val
     } else {
-        rng.gen_range(AL_LB as u64..=AL_UB as u64) as usize
+        rng.gen_range(al_lb as u64..=al_ub as u64) as usize
     };
 
-    if !(AL_LB..=AL_UB).contains(&al) {
+    if !(al_lb..=al_ub).contains(&al) {
         Err(anyhow!("Out of range: {}", al).context("L_A"))?;
     }
 
-    let bl = if let Some(val) = bl {
+    let bl = if let Some(val) = params.bl {
         val
     } else {
         let bl_sqrt = rng.gen_range(BL_LB_SQRT as f64..BL_UB_SQRT as f64);
@@ -427,60 +1065,145 @@ pub fn gen(seed: u64, al: Option<usize>, bl: Option<usize>) -> Result<Input> {
         Err(anyhow!("Out of range: {}", bl).context("L_B"))?;
     }
 
-    let vertex_min_distance: i64 = rng.gen_range(20 as i64..=30 as i64);
-    let edge_max_distance: i64 = rng.gen_range(80 as i64..=140 as i64);
-    let edge_erasing_ratio: f64 = rng.gen_range(0.0..0.5);
+    let vertex_min_distance: i64 = params
+        .vertex_min_distance
+        .unwrap_or_else(|| rng.gen_range(VERTEX_MIN_DISTANCE_LB..=VERTEX_MIN_DISTANCE_UB));
+    if !(1..=COORD_MAX).contains(&vertex_min_distance) {
+        Err(anyhow!("Out of range: {}", vertex_min_distance).context("vertex_min_distance"))?;
+    }
+    let edge_max_distance: i64 = params
+        .edge_max_distance
+        .unwrap_or_else(|| rng.gen_range(EDGE_MAX_DISTANCE_LB..=EDGE_MAX_DISTANCE_UB));
+    if edge_max_distance < 1 {
+        Err(anyhow!("Out of range: {}", edge_max_distance).context("edge_max_distance"))?;
+    }
+    let edge_erasing_ratio: f64 = params
+        .edge_erasing_ratio
+        .unwrap_or_else(|| rng.gen_range(EDGE_ERASING_RATIO_LB..EDGE_ERASING_RATIO_UB));
+    if !(0.0..=1.0).contains(&edge_erasing_ratio) {
+        Err(anyhow!("Out of range: {}", edge_erasing_ratio).context("edge_erasing_ratio"))?;
+    }
 
-    let mut res = construct_planar_graph(
-        N,
-        &mut rng,
-        vertex_min_distance,
-        edge_max_distance,
-        edge_erasing_ratio,
-    );
-    while let None = res {
+    let mut res = None;
+    for _ in 0..MAX_CONSTRUCTION_CNT {
         res = construct_planar_graph(
-            N,
+            n,
             &mut rng,
             vertex_min_distance,
             edge_max_distance,
             edge_erasing_ratio,
         );
+        if res.is_some() {
+            break;
+        }
     }
-    let (edges, coordinates) = res.unwrap();
+    let (edges, coordinates) = res.context(
+        "Failed to construct a connected planar graph. Try fewer vertices or longer edges.",
+    )?;
 
+    // far targets are drawn once the graph is known
     let mut t = vec![];
     let mut now = 0;
-    for _ in 0..T_SZ {
-        let nxt: usize = rng.gen_range(0..(N - 1) as u64) as usize;
-        if nxt < now {
-            t.push(nxt);
-            now = nxt;
-        } else {
-            t.push(nxt + 1);
-            now = nxt + 1;
+    if !params.far_targets {
+        for _ in 0..t_sz {
+            let nxt: usize = rng.gen_range(0..(n - 1) as u64) as usize;
+            if nxt < now {
+                t.push(nxt);
+                now = nxt;
+            } else {
+                t.push(nxt + 1);
+                now = nxt + 1;
+            }
         }
     }
 
-    Ok(Input {
-        n: N,
+    let info = GenInfo {
+        n,
+        m: edges.len(),
+        t_sz,
+        al,
+        bl,
+        vertex_min_distance,
+        edge_max_distance,
+        edge_erasing_ratio,
+    };
+    let mut input = Input {
+        n,
         m: edges.len(),
-        t_sz: T_SZ,
+        t_sz,
         al,
         bl,
         edges,
         t,
         coordinates,
-    })
+    };
+    if params.far_targets {
+        input.t = lib_gen_mode::far_targets(&input, t_sz, &mut rng);
+    }
+    Ok((input, info))
+}
+
+/// Optional layers of `draw_svg_with`
+#[derive(Clone, Debug, Default)]
+pub struct VisOptions {
+    /// Draw a shortest path in this graph of the input from the current vertex to the current
+    /// target. The graph is built once per replay rather than for every frame.
+    pub shortest_path: Option<Graph>,
+    /// Color vertices by a measure over the whole replay instead of their signals
+    pub heatmap: Option<(HeatmapMode, Vec<usize>)>,
 }
 
 pub fn draw_svg(vis_data: &VisData, input: &Input, k: usize) -> SVG {
-    let mut doc = new_svg();
-    doc = draw_graph(vis_data, input, doc);
+    draw_svg_with(vis_data, input, k, &VisOptions::default())
+}
+
+pub fn draw_svg_with(vis_data: &VisData, input: &Input, k: usize, options: &VisOptions) -> SVG {
+    let mut doc = new_svg(svg_height(input));
+    doc = draw_graph(input, doc, options.heatmap.as_ref());
+    doc = draw_array_cells(vis_data, input, doc);
+    doc = draw_turn(vis_data, input, k, options, doc);
+    draw_tooltips(input, doc)
+}
+
+/// The layers of `draw_svg_with` which stay the same over a replay, with an empty group of id
+/// `frame` where the layers drawn by `draw_frame` go
+pub fn draw_background(vis_data: &VisData, input: &Input, options: &VisOptions) -> SVG {
+    let mut doc = new_svg(svg_height(input));
+    doc = draw_graph(input, doc, options.heatmap.as_ref());
+    doc = draw_array_cells(vis_data, input, doc);
+    doc = doc.add(Group::new().set("id", "frame"));
+    draw_tooltips(input, doc)
+}
+
+/// The layers of `draw_svg_with` which change from turn to turn, as the markup of the children
+/// of the `frame` group of `draw_background`
+pub fn draw_frame(vis_data: &VisData, input: &Input, k: usize, options: &VisOptions) -> String {
+    let layer = draw_turn(vis_data, input, k, options, SVG::new());
+    layer
+        .get_children()
+        .iter()
+        .map(|node| node.to_string())
+        .collect()
+}
+
+fn draw_turn(
+    vis_data: &VisData,
+    input: &Input,
+    k: usize,
+    options: &VisOptions,
+    mut doc: SVG,
+) -> SVG {
+    if options.heatmap.is_none() {
+        doc = draw_green_vertices(vis_data, input, doc);
+    }
     doc = draw_cur_v(vis_data, input, doc);
     doc = draw_target(vis_data, input, doc);
     doc = draw_visited_vertices(vis_data, input, doc, k);
-    draw_tooltips(input, doc)
+    if let Some(g) = options.shortest_path.as_ref() {
+        doc = draw_shortest_path(vis_data, input, g, doc);
+    }
+    doc = draw_arrays(vis_data, input, doc);
+    draw_directives(vis_data, input, doc)
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -495,60 +1218,15 @@ pub fn generate(seed: u64, al: Option<usize>, bl: Option<usize>) -> Result<Strin
 #[wasm_bindgen(getter_with_clone)]
 pub struct SolInfo {
     pub error: Option<String>,
+    pub error_cause: Option<String>,
+    pub error_op: Option<usize>,
+    pub error_line: Option<usize>,
+    /// How far the replay got before the error
+    pub progress: Option<String>,
     pub score: usize,
     pub max_turn: usize,
 }
 
-#[cfg(target_arch = "wasm32")]
-#[derive(Clone)]
-struct VisCache {
-    error: Option<String>,
-    vis_data_vec: Vec<VisData>,
-    input: Input,
-}
-
-#[cfg(target_arch = "wasm32")]
-static VIS_CACHE: Lazy<Mutex<Option<VisCache>>> = Lazy::new(|| Mutex::new(None));
-
-#[cfg(target_arch = "wasm32")]
-#[wasm_bindgen]
-pub fn get_sol_info(input_s: &str, output_s: &str, k: usize) -> Result<SolInfo, JsError> {
-    let mut vis_data_vec = vec![];
-    let mut input_res = Input::new();
-    let res = judge(input_s, output_s, &mut vis_data_vec, &mut input_res, k);
-
-    match res {
-        Ok(res) => {
-            *VIS_CACHE.lock().unwrap() = Some(VisCache {
-                error: None,
-                vis_data_vec: vis_data_vec.clone(),
-                input: input_res,
-            });
-
-            let sol_info = SolInfo {
-                error: None,
-                score: res.score,
-                max_turn: vis_data_vec.len(),
-            };
-            Ok(sol_info)
-        }
-        Err(err) => {
-            *VIS_CACHE.lock().unwrap() = Some(VisCache {
-                error: Some(format!("{:#}", err)),
-                vis_data_vec: vis_data_vec.clone(),
-                input: input_res,
-            });
-
-            let sol_info = SolInfo {
-                error: Some(format!("{:#}", err)),
-                score: 0,
-                max_turn: vis_data_vec.len(),
-            };
-            Ok(sol_info)
-        }
-    }
-}
-
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen(getter_with_clone)]
 pub struct VisResult {
@@ -566,19 +1244,76 @@ pub struct VisResult {
     pub comments: Vec<String>,
 }
 
+/// A judged pair of input and output whose turns can be drawn.
+/// Each session owns its replay, so several of them can be used side by side.
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
-pub fn visualize(_input: &str, _output: &str, t: usize, k: usize) -> Result<VisResult, JsError> {
-    console_error_panic_hook::set_once();
+pub struct Session {
+    input: Input,
+    timeline: Timeline,
+    score: usize,
+    error: Option<JudgeError>,
+    progress: Option<Progress>,
+    k: usize,
+}
 
-    let VisCache {
-        error,
-        vis_data_vec,
-        input,
-    } = VIS_CACHE.lock().unwrap().clone().unwrap();
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+impl Session {
+    #[wasm_bindgen(constructor)]
+    pub fn new(input_s: &str, output_s: &str, k: usize) -> Session {
+        console_error_panic_hook::set_once();
+
+        let mut timeline = Timeline::new();
+        let mut input = Input::new();
+        let (score, error, progress) = match judge(input_s, output_s, &mut timeline, &mut input, k)
+        {
+            Ok(res) => (res.score, None, None),
+            Err(failure) => (0, Some(failure.error), failure.progress),
+        };
+
+        Session {
+            input,
+            timeline,
+            score,
+            error,
+            progress,
+            k,
+        }
+    }
+
+    /// The number of turns which can be drawn, including the initial one
+    pub fn max_turn(&self) -> usize {
+        self.timeline.len()
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.error.as_ref().map(|err| format!("{:#}", err))
+    }
+
+    pub fn sol_info(&self) -> SolInfo {
+        SolInfo {
+            error: self.error(),
+            error_cause: self.error.as_ref().map(|err| err.cause().to_string()),
+            error_op: self.error.as_ref().and_then(|err| err.op_idx()),
+            error_line: self.error.as_ref().and_then(|err| err.line()),
+            progress: self.progress.map(|progress| progress.to_string()),
+            score: self.score,
+            max_turn: self.max_turn(),
+        }
+    }
 
-    if t <= vis_data_vec.len() {
-        let vis_data = &vis_data_vec[t];
+    pub fn visualize(&self, t: usize) -> Result<VisResult, JsError> {
+        let Some(vis_data) = self.timeline.get(t) else {
+            return Err(JsError::new(&match self.error() {
+                Some(error) if self.timeline.is_empty() => error,
+                _ => format!("Turn {} is out of range: 0..{}", t, self.max_turn()),
+            }));
+        };
 
         let copy_signals =
             vis_data
@@ -600,11 +1335,11 @@ pub fn visualize(_input: &str, _output: &str, t: usize, k: usize) -> Result<VisR
         };
 
         Ok(VisResult {
-            svg: draw_svg(&vis_data, &input, k).to_string(),
+            svg: draw_svg(&vis_data, &self.input, self.k).to_string(),
             score: vis_data.state.score,
-            t_sz: input.t_sz,
+            t_sz: self.input.t_sz,
             target_idx: vis_data.state.target_idx,
-            target_v: input.t.get(vis_data.state.target_idx).cloned(),
+            target_v: self.input.t.get(vis_data.state.target_idx).cloned(),
             cur_v: vis_data.state.cur_v,
             op: vis_data
                 .commented_op
@@ -616,11 +1351,48 @@ pub fn visualize(_input: &str, _output: &str, t: usize, k: usize) -> Result<VisR
             initial_comments,
             comments,
         })
-    } else {
-        Err(JsError::new(&format!("{:#}", error.unwrap())))
     }
 }
 
+// The session of the latest pair given to the free functions below.
+// Pages call them once per turn with the same pair, which must not be judged again every time.
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    static LAST_SESSION: std::cell::RefCell<Option<(String, String, usize, Session)>> =
+        const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(target_arch = "wasm32")]
+fn with_session<R>(input_s: &str, output_s: &str, k: usize, f: impl FnOnce(&Session) -> R) -> R {
+    LAST_SESSION.with(|last| {
+        let mut last = last.borrow_mut();
+        let cached = matches!(
+            last.as_ref(),
+            Some((input, output, last_k, _)) if input == input_s && output == output_s && *last_k == k
+        );
+        if !cached {
+            let session = Session::new(input_s, output_s, k);
+            *last = Some((input_s.to_string(), output_s.to_string(), k, session));
+        }
+        f(&last.as_ref().unwrap().3)
+    })
+}
+
+/// Same as `Session::new(input_s, output_s, k).sol_info()`.
+/// The session is kept until another pair is given, so this and `visualize` judge the pair once.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn get_sol_info(input_s: &str, output_s: &str, k: usize) -> Result<SolInfo, JsError> {
+    Ok(with_session(input_s, output_s, k, Session::sol_info))
+}
+
+/// Same as `Session::new(input_s, output_s, k).visualize(t)`, reusing the session of the pair
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn visualize(input_s: &str, output_s: &str, t: usize, k: usize) -> Result<VisResult, JsError> {
+    with_session(input_s, output_s, k, |session| session.visualize(t))
+}
+
 struct UnionFind {
     par: Vec<i32>,
 }
@@ -661,6 +1433,7 @@ fn distance2(p1: (i64, i64), p2: (i64, i64)) -> i64 {
 
 type Point = (i64, i64);
 type Line = (Point, Point);
+type Edge = (usize, usize);
 
 fn is_crossing(l1: &Line, l2: &Line) -> bool {
     let &((x0, y0), (x1, y1)) = l1;
@@ -683,10 +1456,10 @@ fn is_crossing(l1: &Line, l2: &Line) -> bool {
         return false;
     }
 
-    return v0 * v1 <= 0 && v2 * v3 <= 0;
+    v0 * v1 <= 0 && v2 * v3 <= 0
 }
 
-fn check_connectivity(n: usize, edges: &Vec<(usize, usize)>, sel: &Vec<bool>) -> bool {
+fn check_connectivity(n: usize, edges: &[(usize, usize)], sel: &[bool]) -> bool {
     let mut uf = UnionFind::new(n);
     for i in 0..sel.len() {
         if sel[i] {
@@ -698,33 +1471,135 @@ fn check_connectivity(n: usize, edges: &Vec<(usize, usize)>, sel: &Vec<bool>) ->
     uf.par[r] == -(n as i32)
 }
 
+// Uniform grid over the coordinate range, used to find nearby points and segments
+struct Grid {
+    cell: i64,
+    w: usize,
+    cells: Vec<Vec<usize>>,
+}
+
+impl Grid {
+    fn new(cell: i64) -> Self {
+        // Keep the number of cells moderate even for tiny distances
+        let cell = cell.max(8);
+        let w = (COORD_MAX / cell + 1) as usize;
+        Grid {
+            cell,
+            w,
+            cells: vec![vec![]; w * w],
+        }
+    }
+
+    // Cells overlapping the rectangle spanned by p0 and p1
+    fn cells(&self, p0: Point, p1: Point) -> impl Iterator<Item = usize> + '_ {
+        let f = |c: i64| (c.clamp(COORD_MIN, COORD_MAX) / self.cell) as usize;
+        let (x0, x1) = (f(p0.0.min(p1.0)), f(p0.0.max(p1.0)));
+        let (y0, y1) = (f(p0.1.min(p1.1)), f(p0.1.max(p1.1)));
+        (x0..=x1).flat_map(move |x| (y0..=y1).map(move |y| x * self.w + y))
+    }
+
+    fn insert(&mut self, id: usize, p0: Point, p1: Point) {
+        let cells = self.cells(p0, p1).collect::<Vec<_>>();
+        for c in cells {
+            self.cells[c].push(id);
+        }
+    }
+
+    // Ids inserted with a rectangle which may overlap the given one. An id can appear twice.
+    fn query(&self, p0: Point, p1: Point) -> impl Iterator<Item = usize> + '_ {
+        self.cells(p0, p1)
+            .flat_map(move |c| self.cells[c].iter().copied())
+    }
+}
+
+// Whether u and v are connected by the selected edges other than `skip`.
+// Searches from both ends and stops as soon as the smaller side is exhausted.
+fn connected_without(
+    adj: &[Vec<(usize, usize)>],
+    sel: &[bool],
+    skip: usize,
+    (u, v): Edge,
+    mark: &mut [usize],
+    stamp: &mut usize,
+) -> bool {
+    *stamp += 2;
+    let sides = [*stamp - 1, *stamp];
+    let mut queues = [vec![u], vec![v]];
+    let mut heads = [0, 0];
+    mark[u] = sides[0];
+    mark[v] = sides[1];
+
+    loop {
+        let s = if queues[0].len() - heads[0] <= queues[1].len() - heads[1] {
+            0
+        } else {
+            1
+        };
+        if heads[s] == queues[s].len() {
+            return false;
+        }
+        let x = queues[s][heads[s]];
+        heads[s] += 1;
+        for &(y, e) in adj[x].iter() {
+            if e == skip || !sel[e] {
+                continue;
+            }
+            if mark[y] == sides[1 - s] {
+                return true;
+            }
+            if mark[y] != sides[s] {
+                mark[y] = sides[s];
+                queues[s].push(y);
+            }
+        }
+    }
+}
+
 fn construct_planar_graph(
     n: usize,
     rng: &mut ChaCha20Rng,
     vertex_min_distance: i64,
     edge_max_distance: i64,
     edge_erasing_ratio: f64,
-) -> Option<(Vec<(usize, usize)>, Vec<(i64, i64)>)> {
-    let mut coords = vec![];
+) -> Option<(Vec<Edge>, Vec<Point>)> {
+    let mut coords: Vec<Point> = vec![];
     let mut edges = vec![];
-    let mut graph = vec![HashSet::new(); n];
 
+    let mut point_grid = Grid::new(vertex_min_distance);
+    let mut trial = 0;
     while coords.len() < n {
+        trial += 1;
+        if trial > MAX_COORD_TRIAL_PER_VERTEX * n {
+            return None;
+        }
         let x = rng.gen_range(0i64..=1000);
         let y = rng.gen_range(0i64..=1000);
-        if coords
-            .iter()
-            .any(|&p| distance2(p, (x, y)) < vertex_min_distance.pow(2))
+        let d = vertex_min_distance;
+        if point_grid
+            .query((x - d, y - d), (x + d, y + d))
+            .any(|i| distance2(coords[i], (x, y)) < d.pow(2))
         {
             continue;
         }
+        point_grid.insert(coords.len(), (x, y), (x, y));
         coords.push((x, y));
     }
 
+    let mut point_grid = Grid::new(edge_max_distance);
+    for (i, &p) in coords.iter().enumerate() {
+        point_grid.insert(i, p, p);
+    }
     let mut short_cands = vec![];
     let mut long_cands = vec![];
     for i in 0..n {
-        for j in i + 1..n {
+        let (x, y) = coords[i];
+        let d = edge_max_distance;
+        let mut js = point_grid
+            .query((x - d, y - d), (x + d, y + d))
+            .filter(|&j| j > i)
+            .collect::<Vec<_>>();
+        js.sort_unstable();
+        for j in js {
             if distance2(coords[i], coords[j]) > edge_max_distance.pow(2) {
                 continue;
             }
@@ -738,32 +1613,31 @@ fn construct_planar_graph(
         }
     }
 
-    let mut cands_list = short_cands;
-    cands_list.shuffle(rng);
-    for (u, v) in cands_list {
-        if edges
-            .iter()
-            .any(|&(e0, e1)| is_crossing(&(coords[u], coords[v]), &(coords[e0], coords[e1])))
-        {
-            continue;
-        }
-        edges.push((u, v));
-        graph[u].insert(v);
-        graph[v].insert(u);
-    }
-
-    cands_list = long_cands;
-    cands_list.shuffle(rng);
-    for (u, v) in cands_list {
-        if edges
-            .iter()
-            .any(|&(e0, e1)| is_crossing(&(coords[u], coords[v]), &(coords[e0], coords[e1])))
-        {
-            continue;
+    // Accepted edges are bucketed by their bounding boxes,
+    // since crossing segments always have overlapping bounding boxes.
+    let mut edge_grid = Grid::new(edge_max_distance);
+    let mut checked = vec![];
+    let mut query_id = 0;
+    for mut cands_list in [short_cands, long_cands] {
+        cands_list.shuffle(rng);
+        for (u, v) in cands_list {
+            let line = (coords[u], coords[v]);
+            query_id += 1;
+            let crossing = edge_grid.query(line.0, line.1).any(|e| {
+                if checked[e] == query_id {
+                    return false;
+                }
+                checked[e] = query_id;
+                let (e0, e1) = edges[e];
+                is_crossing(&line, &(coords[e0], coords[e1]))
+            });
+            if crossing {
+                continue;
+            }
+            edge_grid.insert(edges.len(), line.0, line.1);
+            edges.push((u, v));
+            checked.push(0);
         }
-        edges.push((u, v));
-        graph[u].insert(v);
-        graph[v].insert(u);
     }
 
     edges.shuffle(rng);
@@ -772,9 +1646,19 @@ fn construct_planar_graph(
     if !res {
         return None;
     }
+
+    // While the selected edges keep the graph connected,
+    // removing an edge keeps it connected iff its endpoints stay connected.
+    let mut adj = vec![vec![]; n];
+    for (i, &(u, v)) in edges.iter().enumerate() {
+        adj[u].push((v, i));
+        adj[v].push((u, i));
+    }
+    let mut mark = vec![0; n];
+    let mut stamp = 0;
     for i in 0..edges.len() {
         sel[i] = false;
-        let res = check_connectivity(n, &edges, &sel);
+        let res = connected_without(&adj, &sel, i, edges[i], &mut mark, &mut stamp);
         if res {
             let val = rng.gen_range(0.0..1.0);
             if val > edge_erasing_ratio {
@@ -0,0 +1,112 @@
+use crate::{distance2, Graph, Input};
+
+/// Structural features of an input which explain how hard it is
+#[derive(Clone, Debug)]
+pub struct InputStats {
+    /// `degree_counts[d]` is the number of vertices of degree d
+    pub degree_counts: Vec<usize>,
+    /// The longest shortest path, or `None` if the graph is disconnected
+    pub diameter: Option<usize>,
+    pub bridges: usize,
+    pub articulation_points: usize,
+    pub avg_edge_length: f64,
+    /// The sum of the distances between consecutive targets starting from vertex 0,
+    /// or `None` if some target is unreachable
+    pub total_distance: Option<usize>,
+    pub distinct_targets: usize,
+    /// The number of distinct vertices moved to when following a shortest path to each target.
+    /// It estimates how many vertices A has to contain.
+    pub route_vertices: usize,
+}
+
+impl InputStats {
+    pub fn min_degree(&self) -> usize {
+        self.degree_counts
+            .iter()
+            .position(|&cnt| cnt > 0)
+            .unwrap_or(0)
+    }
+
+    pub fn max_degree(&self) -> usize {
+        self.degree_counts.len().saturating_sub(1)
+    }
+
+    pub fn avg_degree(&self) -> f64 {
+        let n = self.degree_counts.iter().sum::<usize>();
+        let sum = self
+            .degree_counts
+            .iter()
+            .enumerate()
+            .map(|(d, &cnt)| d * cnt)
+            .sum::<usize>();
+        sum as f64 / n.max(1) as f64
+    }
+}
+
+/// Compute the features of `input`.
+/// It runs BFS from every vertex, which takes O(N(N + M)) time.
+pub fn analyze_input(input: &Input) -> InputStats {
+    let g = Graph::new(input);
+    let n = input.n;
+
+    let mut degree_counts = vec![];
+    for v in 0..n {
+        let d = g.degree(v);
+        if degree_counts.len() <= d {
+            degree_counts.resize(d + 1, 0);
+        }
+        degree_counts[d] += 1;
+    }
+
+    let dist = (0..n).map(|v| g.bfs(v)).collect::<Vec<_>>();
+    let diameter = dist
+        .iter()
+        .flatten()
+        .max()
+        .filter(|&&d| d != usize::MAX)
+        .copied();
+
+    let avg_edge_length = input
+        .edges
+        .iter()
+        .map(|&(u, v)| (distance2(input.coordinates[u], input.coordinates[v]) as f64).sqrt())
+        .sum::<f64>()
+        / input.edges.len().max(1) as f64;
+
+    let mut total_distance = Some(0);
+    let mut visited = vec![false; n];
+    let mut cur = 0;
+    for &target in input.t.iter() {
+        let d = dist[target][cur];
+        if d == usize::MAX {
+            total_distance = None;
+            continue;
+        }
+        total_distance = total_distance.map(|total| total + d);
+
+        // The neighbor with the smallest index among those getting closer to the target
+        while cur != target {
+            cur = *g
+                .neighbors(cur)
+                .iter()
+                .find(|&&u| dist[target][u] + 1 == dist[target][cur])
+                .unwrap();
+            visited[cur] = true;
+        }
+    }
+
+    let mut targets = input.t.clone();
+    targets.sort_unstable();
+    targets.dedup();
+
+    InputStats {
+        degree_counts,
+        diameter,
+        bridges: g.bridges().len(),
+        articulation_points: g.articulation_points().len(),
+        avg_edge_length,
+        total_distance,
+        distinct_targets: targets.len(),
+        route_vertices: visited.iter().filter(|&&b| b).count(),
+    }
+}
@@ -0,0 +1,67 @@
+use crate::{Graph, Input};
+
+/// A lower bound on the number of signal operations of any valid output for `input`.
+///
+/// Split the moves into phases, each of which starts with a signal operation, so an output has
+/// at least as many signal operations as phases. B does not change during a phase, so the green
+/// vertices form a fixed set S of at most L_B vertices and every move of the phase enters S:
+///
+/// - the targets reached in one phase are in S, so there are at most L_B distinct ones, and
+///   the walk between two of them stays in S, so they are within distance L_B - 1,
+/// - the walk of a phase visits at most L_B vertices besides the one it starts from, so it
+///   moves the current vertex by a distance of at most L_B, and by at most L_B - 1 after
+///   reaching a target, which is in S.
+///
+/// Hence if t_a is reached in a later phase than t_{a-1}, at distance d from it, the rest of
+/// the phase of t_{a-1} covers at most L_B - 1 of d, so at least
+/// max(1, ceil((d + 1 - L_B) / L_B)) phases start until t_a is reached. Likewise t_0 takes at
+/// least max(1, ceil(d / L_B)) phases from vertex 0. Targets reached in a phase are consecutive
+/// in `t`, so every output is counted by some partition of `t` into groups, and the DP takes
+/// the minimum over all of them.
+pub fn copy_lower_bound(input: &Input) -> usize {
+    let t = &input.t;
+    let bl = input.bl;
+    if t.is_empty() {
+        return 0;
+    }
+    if bl == 0 {
+        // No vertex can turn green, which no output overcomes
+        return usize::MAX;
+    }
+
+    let g = Graph::new(input);
+    let mut dist = vec![vec![]; input.n];
+    for &v in std::iter::once(&0).chain(t.iter()) {
+        if dist[v].is_empty() {
+            dist[v] = g.bfs(v);
+        }
+    }
+    if t.iter().any(|&v| dist[0][v] == usize::MAX) {
+        return usize::MAX;
+    }
+
+    // dp[i]: the minimum number of phases until the phase reaching t_i ends
+    let mut dp = vec![usize::MAX; t.len()];
+    for i in 0..t.len() {
+        // the group t_a..=t_i is reached in one phase
+        let mut group: Vec<usize> = vec![];
+        for a in (0..=i).rev() {
+            if !group.contains(&t[a]) {
+                if group.len() == bl || group.iter().any(|&u| dist[u][t[a]] >= bl) {
+                    break;
+                }
+                group.push(t[a]);
+            }
+
+            let phases = if a == 0 {
+                dist[0][t[0]].div_ceil(bl).max(1)
+            } else {
+                let d = dist[t[a - 1]][t[a]];
+                dp[a - 1] + (d + 1).saturating_sub(bl).div_ceil(bl).max(1)
+            };
+            dp[i] = dp[i].min(phases);
+        }
+    }
+
+    dp[t.len() - 1]
+}
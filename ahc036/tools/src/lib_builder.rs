@@ -0,0 +1,165 @@
+use anyhow::{bail, ensure, Context, Result};
+
+use crate::{validate_input, Input, BL_LB_SQRT, COORD_MAX, COORD_MIN};
+
+/// Builds a small input by hand.
+///
+/// Vertices are numbered in the order they are added.
+/// L_A defaults to N and L_B defaults to the smallest value the generator uses.
+#[derive(Clone, Debug, Default)]
+pub struct InputBuilder {
+    coordinates: Vec<(i64, i64)>,
+    edges: Vec<(usize, usize)>,
+    t: Vec<usize>,
+    al: Option<usize>,
+    bl: Option<usize>,
+}
+
+impl InputBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn vertex(&mut self, x: i64, y: i64) -> &mut Self {
+        self.coordinates.push((x, y));
+        self
+    }
+
+    pub fn edge(&mut self, u: usize, v: usize) -> &mut Self {
+        self.edges.push((u, v));
+        self
+    }
+
+    pub fn target(&mut self, v: usize) -> &mut Self {
+        self.t.push(v);
+        self
+    }
+
+    pub fn set_al(&mut self, al: usize) -> &mut Self {
+        self.al = Some(al);
+        self
+    }
+
+    pub fn set_bl(&mut self, bl: usize) -> &mut Self {
+        self.bl = Some(bl);
+        self
+    }
+
+    /// Build an input satisfying every guarantee checked by `validate_input`
+    pub fn build(&self) -> Result<Input> {
+        let input = self.build_unchecked()?;
+        let violations = validate_input(&input);
+        if !violations.is_empty() {
+            let messages = violations.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+            bail!("{}", messages.join("\n"));
+        }
+        Ok(input)
+    }
+
+    /// Build an input which `parse_input` accepts, without the guarantees of the generator.
+    /// This is meant for cases the generator never makes, such as repeated targets.
+    pub fn build_unchecked(&self) -> Result<Input> {
+        let n = self.coordinates.len();
+        ensure!(n > 0, "At least one vertex is needed.");
+        for (i, &(x, y)) in self.coordinates.iter().enumerate() {
+            ensure!(
+                (COORD_MIN..=COORD_MAX).contains(&x) && (COORD_MIN..=COORD_MAX).contains(&y),
+                "Vertex {}: ({}, {}) is out of range.",
+                i,
+                x,
+                y
+            );
+        }
+        for (i, &(u, v)) in self.edges.iter().enumerate() {
+            ensure!(
+                u < n && v < n,
+                "Edge {}: ({}, {}) is out of range.",
+                i,
+                u,
+                v
+            );
+        }
+        ensure!(
+            self.edges.len() >= n - 1,
+            "At least N - 1 = {} edges are needed.",
+            n - 1
+        );
+        for (i, &v) in self.t.iter().enumerate() {
+            ensure!(v < n, "t_{} = {} is out of range.", i, v);
+        }
+
+        Ok(Input {
+            n,
+            m: self.edges.len(),
+            t_sz: self.t.len(),
+            al: self.al.unwrap_or(n),
+            bl: self.bl.unwrap_or(BL_LB_SQRT.pow(2)),
+            edges: self.edges.clone(),
+            t: self.t.clone(),
+            coordinates: self.coordinates.clone(),
+        })
+    }
+}
+
+/// Read an `InputBuilder` from a compact text, one command per line:
+///
+/// ```text
+/// # a comment
+/// v X Y        add a vertex
+/// e V_1 V_2 .. add the edges of the path V_1 - V_2 - ..
+/// t V_1 V_2 .. append targets
+/// la L_A
+/// lb L_B
+/// ```
+pub fn parse_input_dsl(s: &str) -> Result<InputBuilder> {
+    let mut builder = InputBuilder::new();
+    for (i, line) in s.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        parse_dsl_line(&mut builder, line).with_context(|| format!("line {}", i + 1))?;
+    }
+    Ok(builder)
+}
+
+fn parse_dsl_line(builder: &mut InputBuilder, line: &str) -> Result<()> {
+    let mut tokens = line.split_whitespace();
+    let command = tokens.next().unwrap();
+    let args = tokens
+        .map(|token| {
+            token
+                .parse::<i64>()
+                .with_context(|| format!("Parse error: {}", token))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let index = |v: i64| usize::try_from(v).with_context(|| format!("Out of range: {}", v));
+
+    match (command, &args[..]) {
+        ("v", &[x, y]) => {
+            builder.vertex(x, y);
+        }
+        ("e", path) if path.len() >= 2 => {
+            for w in path.windows(2) {
+                builder.edge(index(w[0])?, index(w[1])?);
+            }
+        }
+        ("t", targets) => {
+            for &v in targets.iter() {
+                builder.target(index(v)?);
+            }
+        }
+        ("la", &[al]) => {
+            builder.set_al(index(al)?);
+        }
+        ("lb", &[bl]) => {
+            builder.set_bl(index(bl)?);
+        }
+        ("v", _) => bail!("Usage: v X Y"),
+        ("e", _) => bail!("Usage: e V_1 V_2 .."),
+        ("la", _) => bail!("Usage: la L_A"),
+        ("lb", _) => bail!("Usage: lb L_B"),
+        _ => bail!("Unknown command: {}", command),
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+use crate::Input;
+
+const MARK_COLOR: &str = "magenta";
+const PATH_COLOR: &str = "purple";
+
+/// An annotation written in a comment of an output, drawn on the frame the comment is attached to.
+/// A comment starting with `@` is a directive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Directive {
+    /// `@mark v ID [COLOR]`: circle vertex ID
+    MarkVertex { v: usize, color: String },
+    /// `@mark a POS [LEN] [COLOR]`: outline A[POS..POS + LEN]
+    MarkA { p: usize, len: usize, color: String },
+    /// `@mark b POS [LEN] [COLOR]`: outline B[POS..POS + LEN]
+    MarkB { p: usize, len: usize, color: String },
+    /// `@path V_1 V_2 ... [COLOR]`: draw the line through the vertices
+    Path { vertices: Vec<usize>, color: String },
+    /// `@text TEXT`: write TEXT, which may be enclosed in double quotes
+    Text(String),
+}
+
+/// Parse `comment` as a directive for `input`.
+/// Returns `None` if it is not a directive, and an error message if it is a malformed one.
+pub fn parse_directive(comment: &str, input: &Input) -> Option<Result<Directive, String>> {
+    let rest = comment.strip_prefix('@')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let res = match name {
+        "mark" => parse_mark(args, input),
+        "path" => parse_path(args, input),
+        "text" => {
+            let text = args.trim();
+            let text = text
+                .strip_prefix('"')
+                .and_then(|text| text.strip_suffix('"'))
+                .unwrap_or(text);
+            Ok(Directive::Text(text.to_string()))
+        }
+        _ => Err(format!("Unknown directive: @{}", name)),
+    };
+    Some(res)
+}
+
+// Split off the trailing color, which is any token other than a number
+fn split_color<'a>(
+    mut tokens: Vec<&'a str>,
+    default: &str,
+) -> Result<(Vec<&'a str>, String), String> {
+    match tokens.last() {
+        Some(&last) if last.parse::<usize>().is_err() => {
+            if !last.chars().all(|c| c.is_ascii_alphanumeric() || c == '#') {
+                return Err(format!("Invalid color: {}", last));
+            }
+            tokens.pop();
+            Ok((tokens, last.to_string()))
+        }
+        _ => Ok((tokens, default.to_string())),
+    }
+}
+
+fn parse_number(token: &str, ub: usize, name: &str) -> Result<usize, String> {
+    match token.parse::<usize>() {
+        Ok(v) if v < ub => Ok(v),
+        Ok(v) => Err(format!("{} = {} must be less than {}", name, v, ub)),
+        Err(_) => Err(format!("{} must be a number: {}", name, token)),
+    }
+}
+
+fn parse_mark(args: &str, input: &Input) -> Result<Directive, String> {
+    let tokens = args.split_whitespace().collect::<Vec<_>>();
+    let Some((&kind, tokens)) = tokens.split_first() else {
+        return Err("@mark needs v, a or b".to_string());
+    };
+    let (tokens, color) = split_color(tokens.to_vec(), MARK_COLOR)?;
+
+    match kind {
+        "v" => match tokens[..] {
+            [v] => Ok(Directive::MarkVertex {
+                v: parse_number(v, input.n, "ID")?,
+                color,
+            }),
+            _ => Err("Usage: @mark v ID [COLOR]".to_string()),
+        },
+        "a" | "b" => {
+            let size = if kind == "a" { input.al } else { input.bl };
+            let (p, len) = match tokens[..] {
+                [p] => (parse_number(p, size, "POS")?, 1),
+                [p, len] => (
+                    parse_number(p, size, "POS")?,
+                    parse_number(len, size + 1, "LEN")?,
+                ),
+                _ => return Err(format!("Usage: @mark {} POS [LEN] [COLOR]", kind)),
+            };
+            if p + len > size {
+                return Err(format!("POS + LEN = {} must be at most {}", p + len, size));
+            }
+            if kind == "a" {
+                Ok(Directive::MarkA { p, len, color })
+            } else {
+                Ok(Directive::MarkB { p, len, color })
+            }
+        }
+        _ => Err(format!("@mark needs v, a or b: {}", kind)),
+    }
+}
+
+fn parse_path(args: &str, input: &Input) -> Result<Directive, String> {
+    let tokens = args.split_whitespace().collect::<Vec<_>>();
+    let (tokens, color) = split_color(tokens, PATH_COLOR)?;
+    if tokens.is_empty() {
+        return Err("Usage: @path V_1 V_2 ... [COLOR]".to_string());
+    }
+    let vertices = tokens
+        .iter()
+        .map(|v| parse_number(v, input.n, "V"))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Directive::Path { vertices, color })
+}
@@ -0,0 +1,236 @@
+use anyhow::{ensure, Context, Result};
+use serde::Deserialize;
+use std::{fmt, str};
+
+use crate::{Input, COORD_MAX, COORD_MIN};
+
+/// A format which an input can be written in besides the text of the problem statement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT with `pos` attributes, to be drawn by `neato -n`
+    Dot,
+    GraphMl,
+    /// `{"LA", "LB", "edges", "targets", "coordinates"}`, which `input_from_json` reads back
+    Json,
+}
+
+impl GraphFormat {
+    pub const ALL: [GraphFormat; 3] = [GraphFormat::Dot, GraphFormat::GraphMl, GraphFormat::Json];
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphFormat::Dot => write!(f, "dot"),
+            GraphFormat::GraphMl => write!(f, "graphml"),
+            GraphFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GraphFormat::ALL
+            .into_iter()
+            .find(|format| format.to_string() == s)
+            .ok_or_else(|| {
+                let names = GraphFormat::ALL.map(|format| format.to_string());
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+pub fn export_input(input: &Input, format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Dot => to_dot(input),
+        GraphFormat::GraphMl => to_graphml(input),
+        GraphFormat::Json => to_json(input),
+    }
+}
+
+// The number of times each vertex appears in t
+fn target_counts(input: &Input) -> Vec<usize> {
+    let mut counts = vec![0; input.n];
+    for &v in input.t.iter() {
+        counts[v] += 1;
+    }
+    counts
+}
+
+fn to_dot(input: &Input) -> String {
+    let mut s = String::new();
+    s += &format!(
+        "// N = {}, M = {}, T = {}, L_A = {}, L_B = {}\n",
+        input.n, input.m, input.t_sz, input.al, input.bl
+    );
+    s += "graph G {\n";
+    s += "  node [shape=circle, width=0.15, fixedsize=true, fontsize=6];\n";
+    let counts = target_counts(input);
+    for (v, &(x, y)) in input.coordinates.iter().enumerate() {
+        // Graphviz puts the y axis upward
+        s += &format!(
+            "  {} [pos=\"{},{}!\", target_count={}{}];\n",
+            v,
+            x,
+            COORD_MAX - y,
+            counts[v],
+            if v == 0 { ", style=filled" } else { "" }
+        );
+    }
+    for &(u, v) in input.edges.iter() {
+        s += &format!("  {} -- {};\n", u, v);
+    }
+    s += "}\n";
+    s
+}
+
+fn to_graphml(input: &Input) -> String {
+    let mut s = String::new();
+    s += "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+    s += "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n";
+    for (id, target, ty) in [
+        ("LA", "graph", "int"),
+        ("LB", "graph", "int"),
+        ("targets", "graph", "string"),
+        ("x", "node", "int"),
+        ("y", "node", "int"),
+        ("target_count", "node", "int"),
+    ] {
+        s += &format!(
+            "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>\n",
+            id, target, ty
+        );
+    }
+    s += "  <graph id=\"G\" edgedefault=\"undirected\">\n";
+    s += &format!("    <data key=\"LA\">{}</data>\n", input.al);
+    s += &format!("    <data key=\"LB\">{}</data>\n", input.bl);
+    let targets = input.t.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    s += &format!("    <data key=\"targets\">{}</data>\n", targets.join(" "));
+    let counts = target_counts(input);
+    for (v, &(x, y)) in input.coordinates.iter().enumerate() {
+        s += &format!(
+            "    <node id=\"n{}\"><data key=\"x\">{}</data><data key=\"y\">{}</data><data key=\"target_count\">{}</data></node>\n",
+            v, x, y, counts[v]
+        );
+    }
+    for (i, &(u, v)) in input.edges.iter().enumerate() {
+        s += &format!(
+            "    <edge id=\"e{}\" source=\"n{}\" target=\"n{}\"/>\n",
+            i, u, v
+        );
+    }
+    s += "  </graph>\n";
+    s += "</graphml>\n";
+    s
+}
+
+fn to_json(input: &Input) -> String {
+    let pairs = |pairs: Vec<String>| format!("[\n    {}\n  ]", pairs.join(",\n    "));
+    let edges = input
+        .edges
+        .iter()
+        .map(|&(u, v)| format!("[{}, {}]", u, v))
+        .collect();
+    let coordinates = input
+        .coordinates
+        .iter()
+        .map(|&(x, y)| format!("[{}, {}]", x, y))
+        .collect();
+    let targets = input.t.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    format!(
+        "{{\n  \"N\": {},\n  \"M\": {},\n  \"T\": {},\n  \"LA\": {},\n  \"LB\": {},\n  \"edges\": {},\n  \"targets\": [{}],\n  \"coordinates\": {}\n}}\n",
+        input.n,
+        input.m,
+        input.t_sz,
+        input.al,
+        input.bl,
+        pairs(edges),
+        targets.join(", "),
+        pairs(coordinates)
+    )
+}
+
+// The members of the JSON written by `to_json`. Other members are ignored.
+#[derive(Deserialize)]
+struct JsonInput {
+    #[serde(rename = "N")]
+    n: Option<usize>,
+    #[serde(rename = "M")]
+    m: Option<usize>,
+    #[serde(rename = "T")]
+    t_sz: Option<usize>,
+    #[serde(rename = "LA")]
+    al: usize,
+    #[serde(rename = "LB")]
+    bl: usize,
+    edges: Vec<(i64, i64)>,
+    targets: Vec<i64>,
+    coordinates: Vec<(i64, i64)>,
+}
+
+/// Read an input from the JSON written by `GraphFormat::Json`.
+/// N, M and T are optional, and they must match the lengths of the arrays if given.
+pub fn input_from_json(s: &str) -> Result<Input> {
+    let json = serde_json::from_str::<JsonInput>(s)?;
+
+    for (i, &(x, y)) in json.coordinates.iter().enumerate() {
+        ensure!(
+            (COORD_MIN..=COORD_MAX).contains(&x) && (COORD_MIN..=COORD_MAX).contains(&y),
+            "coordinates[{}]: ({}, {}) is out of range",
+            i,
+            x,
+            y
+        );
+    }
+    let n = json.coordinates.len();
+    ensure!(n > 0, "coordinates must not be empty");
+
+    let vertex = |v: i64| -> Result<usize> {
+        ensure!((0..n as i64).contains(&v), "Vertex {} is out of range", v);
+        Ok(v as usize)
+    };
+    let edges = json
+        .edges
+        .iter()
+        .enumerate()
+        .map(|(i, &(u, v))| {
+            let res = vertex(u).and_then(|u| Ok((u, vertex(v)?)));
+            res.with_context(|| format!("edges[{}]", i))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let t = json
+        .targets
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| vertex(v).with_context(|| format!("targets[{}]", i)))
+        .collect::<Result<Vec<_>>>()?;
+
+    for (key, value, len) in [
+        ("N", json.n, n),
+        ("M", json.m, edges.len()),
+        ("T", json.t_sz, t.len()),
+    ] {
+        if let Some(value) = value {
+            ensure!(
+                value == len,
+                "{} = {} does not match the data ({})",
+                key,
+                value,
+                len
+            );
+        }
+    }
+
+    Ok(Input {
+        n,
+        m: edges.len(),
+        t_sz: t.len(),
+        al: json.al,
+        bl: json.bl,
+        edges,
+        t,
+        coordinates: json.coordinates,
+    })
+}
@@ -0,0 +1,117 @@
+use rand::Rng;
+use rand_chacha::ChaCha20Rng;
+use std::{fmt, str};
+
+use crate::{
+    GenParams, Graph, Input, BL_LB_SQRT, BL_UB_SQRT, EDGE_MAX_DISTANCE_LB, N,
+    VERTEX_MIN_DISTANCE_LB,
+};
+
+/// A preset of `gen_with` for one of the hard cases
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GenMode {
+    /// L_A = N
+    MinLa,
+    /// L_A = 2N
+    MaxLa,
+    /// The smallest L_B
+    MinLb,
+    /// The largest L_B
+    MaxLb,
+    /// Every removable edge is erased, leaving a spanning tree
+    Tree,
+    /// Vertices packed as close as allowed, with short edges and no edge erased
+    Dense,
+    /// Each target is one of the vertices farthest from the previous one
+    FarTargets,
+}
+
+impl GenMode {
+    pub const ALL: [GenMode; 7] = [
+        GenMode::MinLa,
+        GenMode::MaxLa,
+        GenMode::MinLb,
+        GenMode::MaxLb,
+        GenMode::Tree,
+        GenMode::Dense,
+        GenMode::FarTargets,
+    ];
+
+    /// Fix the values of `params` this mode is about.
+    /// Values which are already fixed are left as they are.
+    pub fn apply(self, params: &mut GenParams) {
+        let n = params.n.unwrap_or(N);
+        match self {
+            GenMode::MinLa => {
+                params.al.get_or_insert(n);
+            }
+            GenMode::MaxLa => {
+                params.al.get_or_insert(n * 2);
+            }
+            GenMode::MinLb => {
+                params.bl.get_or_insert(BL_LB_SQRT.pow(2));
+            }
+            GenMode::MaxLb => {
+                params.bl.get_or_insert(BL_UB_SQRT.pow(2) - 1);
+            }
+            GenMode::Tree => {
+                params.edge_erasing_ratio.get_or_insert(1.0);
+            }
+            GenMode::Dense => {
+                params
+                    .vertex_min_distance
+                    .get_or_insert(VERTEX_MIN_DISTANCE_LB);
+                params.edge_max_distance.get_or_insert(EDGE_MAX_DISTANCE_LB);
+                params.edge_erasing_ratio.get_or_insert(0.0);
+            }
+            GenMode::FarTargets => params.far_targets = true,
+        }
+    }
+}
+
+impl fmt::Display for GenMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GenMode::MinLa => write!(f, "min-la"),
+            GenMode::MaxLa => write!(f, "max-la"),
+            GenMode::MinLb => write!(f, "min-lb"),
+            GenMode::MaxLb => write!(f, "max-lb"),
+            GenMode::Tree => write!(f, "tree"),
+            GenMode::Dense => write!(f, "dense"),
+            GenMode::FarTargets => write!(f, "far-targets"),
+        }
+    }
+}
+
+impl str::FromStr for GenMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GenMode::ALL
+            .into_iter()
+            .find(|mode| mode.to_string() == s)
+            .ok_or_else(|| {
+                let names = GenMode::ALL.map(|mode| mode.to_string());
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Draw `t_sz` targets of `input`, each at a BFS distance of at least 3/4 of the largest one
+/// from the previous target. The first one is measured from vertex 0.
+pub(crate) fn far_targets(input: &Input, t_sz: usize, rng: &mut ChaCha20Rng) -> Vec<usize> {
+    let g = Graph::new(input);
+    let mut t = vec![];
+    let mut now = 0;
+    for _ in 0..t_sz {
+        let dist = g.bfs(now);
+        let max_dist = dist.iter().copied().filter(|&d| d != usize::MAX).max();
+        let lb = (max_dist.unwrap_or(0) * 3).div_ceil(4).max(1);
+        let candidates = (0..input.n)
+            .filter(|&v| dist[v] != usize::MAX && dist[v] >= lb)
+            .collect::<Vec<_>>();
+        now = candidates[rng.gen_range(0..candidates.len())];
+        t.push(now);
+    }
+    t
+}
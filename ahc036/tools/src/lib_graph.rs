@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+
+use crate::Input;
+
+/// Undirected graph of an input in CSR form. Neighbors of each vertex are sorted.
+#[derive(Clone, Debug)]
+pub struct Graph {
+    start: Vec<usize>,
+    adj: Vec<usize>,
+}
+
+impl Graph {
+    pub fn new(input: &Input) -> Self {
+        let n = input.n;
+        let mut deg = vec![0; n];
+        for &(u, v) in input.edges.iter() {
+            deg[u] += 1;
+            deg[v] += 1;
+        }
+
+        let mut start = vec![0; n + 1];
+        for i in 0..n {
+            start[i + 1] = start[i] + deg[i];
+        }
+
+        let mut adj = vec![0; start[n]];
+        let mut pos = start.clone();
+        for &(u, v) in input.edges.iter() {
+            adj[pos[u]] = v;
+            pos[u] += 1;
+            adj[pos[v]] = u;
+            pos[v] += 1;
+        }
+        for i in 0..n {
+            adj[start[i]..start[i + 1]].sort_unstable();
+        }
+
+        Graph { start, adj }
+    }
+
+    pub fn n(&self) -> usize {
+        self.start.len() - 1
+    }
+
+    pub fn neighbors(&self, v: usize) -> &[usize] {
+        &self.adj[self.start[v]..self.start[v + 1]]
+    }
+
+    pub fn degree(&self, v: usize) -> usize {
+        self.start[v + 1] - self.start[v]
+    }
+
+    pub fn is_adjacent(&self, u: usize, v: usize) -> bool {
+        self.neighbors(u).binary_search(&v).is_ok()
+    }
+
+    /// Distances from `s` to every vertex. Unreachable vertices have `usize::MAX`.
+    pub fn bfs(&self, s: usize) -> Vec<usize> {
+        let mut dist = vec![usize::MAX; self.n()];
+        let mut que = VecDeque::new();
+        dist[s] = 0;
+        que.push_back(s);
+        while let Some(v) = que.pop_front() {
+            for &u in self.neighbors(v) {
+                if dist[u] == usize::MAX {
+                    dist[u] = dist[v] + 1;
+                    que.push_back(u);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Vertices of a shortest path from `s` to `t` excluding `s`, or `None` if `t` is unreachable.
+    /// Among shortest paths, the one taking the smallest neighbor at each step is chosen.
+    pub fn shortest_path(&self, s: usize, t: usize) -> Option<Vec<usize>> {
+        let dist = self.bfs(t);
+        if dist[s] == usize::MAX {
+            return None;
+        }
+        let mut path = vec![];
+        let mut v = s;
+        while v != t {
+            v = *self
+                .neighbors(v)
+                .iter()
+                .find(|&&u| dist[u] + 1 == dist[v])
+                .unwrap();
+            path.push(v);
+        }
+        Some(path)
+    }
+
+    /// Edges whose removal disconnects their endpoints, as `(u, v)` with `u < v`
+    pub fn bridges(&self) -> Vec<(usize, usize)> {
+        let (ord, low, parent) = self.lowlink();
+        let mut bridges = vec![];
+        for v in 0..self.n() {
+            let p = parent[v];
+            if p != usize::MAX && low[v] > ord[p] {
+                bridges.push((p.min(v), p.max(v)));
+            }
+        }
+        bridges.sort_unstable();
+        bridges
+    }
+
+    /// Vertices whose removal increases the number of connected components
+    pub fn articulation_points(&self) -> Vec<usize> {
+        let (ord, low, parent) = self.lowlink();
+        let mut is_cut = vec![false; self.n()];
+        let mut root_children = vec![0; self.n()];
+        for v in 0..self.n() {
+            let p = parent[v];
+            if p == usize::MAX {
+                continue;
+            }
+            if parent[p] == usize::MAX {
+                root_children[p] += 1;
+            } else if low[v] >= ord[p] {
+                is_cut[p] = true;
+            }
+        }
+        (0..self.n())
+            .filter(|&v| is_cut[v] || root_children[v] >= 2)
+            .collect()
+    }
+
+    // Preorder, lowlink and DFS parent of each vertex. Roots have `usize::MAX` as the parent.
+    fn lowlink(&self) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+        let n = self.n();
+        let mut ord = vec![usize::MAX; n];
+        let mut low = vec![usize::MAX; n];
+        let mut parent = vec![usize::MAX; n];
+        // the edge to the parent is skipped only once, so that parallel edges are kept
+        let mut parent_skipped = vec![false; n];
+        let mut next = self.start[..n].to_vec();
+        let mut cnt = 0;
+
+        for r in 0..n {
+            if ord[r] != usize::MAX {
+                continue;
+            }
+            ord[r] = cnt;
+            low[r] = cnt;
+            cnt += 1;
+            let mut stack = vec![r];
+            while let Some(&v) = stack.last() {
+                if next[v] < self.start[v + 1] {
+                    let u = self.adj[next[v]];
+                    next[v] += 1;
+                    if u == parent[v] && !parent_skipped[v] {
+                        parent_skipped[v] = true;
+                    } else if ord[u] == usize::MAX {
+                        parent[u] = v;
+                        ord[u] = cnt;
+                        low[u] = cnt;
+                        cnt += 1;
+                        stack.push(u);
+                    } else {
+                        low[v] = low[v].min(ord[u]);
+                    }
+                } else {
+                    stack.pop();
+                    if let Some(&p) = stack.last() {
+                        low[p] = low[p].min(low[v]);
+                    }
+                }
+            }
+        }
+
+        (ord, low, parent)
+    }
+}
@@ -0,0 +1,119 @@
+use std::{fmt, str};
+
+use crate::{CopySignals, Input, Observer, State};
+
+/// A per-vertex measure over a whole replay
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeatmapMode {
+    /// The number of moves to the vertex
+    Visits,
+    /// The number of turns in which the vertex was green, including the initial one
+    GreenTurns,
+    /// The number of copy operations which turned the vertex from red to green
+    TurnedGreen,
+}
+
+impl HeatmapMode {
+    pub const ALL: [HeatmapMode; 3] = [
+        HeatmapMode::Visits,
+        HeatmapMode::GreenTurns,
+        HeatmapMode::TurnedGreen,
+    ];
+}
+
+impl fmt::Display for HeatmapMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HeatmapMode::Visits => write!(f, "visits"),
+            HeatmapMode::GreenTurns => write!(f, "green-turns"),
+            HeatmapMode::TurnedGreen => write!(f, "turned-green"),
+        }
+    }
+}
+
+impl str::FromStr for HeatmapMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HeatmapMode::ALL
+            .into_iter()
+            .find(|mode| mode.to_string() == s)
+            .ok_or_else(|| {
+                let names = HeatmapMode::ALL.map(|mode| mode.to_string());
+                format!("expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Collects every measure of `HeatmapMode` for each vertex
+#[derive(Clone, Debug, Default)]
+pub struct Heatmap {
+    pub visits: Vec<usize>,
+    pub green_turns: Vec<usize>,
+    pub turned_green: Vec<usize>,
+    // whether each vertex was green before the latest operation
+    green: Vec<bool>,
+}
+
+impl Heatmap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn values(&self, mode: HeatmapMode) -> &[usize] {
+        match mode {
+            HeatmapMode::Visits => &self.visits,
+            HeatmapMode::GreenTurns => &self.green_turns,
+            HeatmapMode::TurnedGreen => &self.turned_green,
+        }
+    }
+
+    fn count_green(&mut self, state: &State) {
+        for (v, &cnt) in state.traffic_light().iter().enumerate() {
+            if cnt > 0 {
+                self.green_turns[v] += 1;
+            }
+        }
+    }
+}
+
+impl Observer for Heatmap {
+    fn on_init(&mut self, input: &Input, state: &State, _comments: &[String]) {
+        *self = Heatmap {
+            visits: vec![0; input.n],
+            green_turns: vec![0; input.n],
+            turned_green: vec![0; input.n],
+            green: vec![false; input.n],
+        };
+        self.count_green(state);
+    }
+
+    fn on_copy_signals(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        _cs: CopySignals,
+        _comments: &[String],
+    ) {
+        for (v, &cnt) in state.traffic_light().iter().enumerate() {
+            if cnt > 0 && !self.green[v] {
+                self.turned_green[v] += 1;
+            }
+            self.green[v] = cnt > 0;
+        }
+        self.count_green(state);
+    }
+
+    fn on_move(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        v: usize,
+        _comments: &[String],
+    ) {
+        self.visits[v] += 1;
+        self.count_green(state);
+    }
+}
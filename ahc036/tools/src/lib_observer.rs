@@ -0,0 +1,62 @@
+use crate::{CopySignals, Input, JudgeError, State};
+
+/// Callbacks invoked by `judge_with` while it replays an output.
+///
+/// `state` is always the state right after the reported event.
+/// A rejected operation is never applied, so it changes nothing in `state`, not even `op_cnt` or
+/// `score`, and is only reported to `on_error`.
+/// Every method does nothing by default, so an observer only implements what it needs.
+pub trait Observer {
+    /// Called once before the first operation. `state.a()` is the initial array A.
+    fn on_init(&mut self, _input: &Input, _state: &State, _comments: &[String]) {}
+
+    fn on_copy_signals(
+        &mut self,
+        _input: &Input,
+        _state: &State,
+        _op_idx: usize,
+        _cs: CopySignals,
+        _comments: &[String],
+    ) {
+    }
+
+    fn on_move(
+        &mut self,
+        _input: &Input,
+        _state: &State,
+        _op_idx: usize,
+        _v: usize,
+        _comments: &[String],
+    ) {
+    }
+
+    /// Called after `on_move` when the move reached `input.t()[target_idx]`
+    fn on_target_reached(&mut self, _input: &Input, _state: &State, _target_idx: usize) {}
+
+    /// Called when the replay is rejected. `state` is the last valid state.
+    fn on_error(&mut self, _input: &Input, _state: &State, _error: &JudgeError) {}
+}
+
+/// Counts the copy operations, which make the score of an accepted output.
+/// `judge_with` computes its score with one of these.
+#[derive(Clone, Debug, Default)]
+pub struct Scorer {
+    pub score: usize,
+}
+
+impl Observer for Scorer {
+    fn on_init(&mut self, _input: &Input, _state: &State, _comments: &[String]) {
+        self.score = 0;
+    }
+
+    fn on_copy_signals(
+        &mut self,
+        _input: &Input,
+        _state: &State,
+        _op_idx: usize,
+        _cs: CopySignals,
+        _comments: &[String],
+    ) {
+        self.score += 1;
+    }
+}
@@ -0,0 +1,315 @@
+use anyhow::{bail, Result};
+
+use crate::{CopySignals, Input, Op, Output, OutputBuilder, State};
+
+// Copy operations, each made just before the move of the given index
+pub(crate) type Plan = Vec<(usize, CopySignals)>;
+
+/// Rebuild the signal operations of `output` greedily.
+///
+/// The array A and the sequence of moves are kept as they are, and the copy operations are
+/// chosen again among every `s l P_A P_B`. The fewest copies found by `greedy_plan`, by
+/// `full_width_plan` and in `output` itself (if it is valid) are used, so the result is never
+/// worse than a valid `output`. It is a heuristic and does not always reach the minimum.
+/// Comments of removed copy operations are attached to the next operation.
+pub fn greedy_reschedule(input: &Input, output: &Output) -> Result<Output> {
+    let a = output.initial_a();
+    let moves = output
+        .commented_ops()
+        .iter()
+        .filter_map(|commented_op| match commented_op.op {
+            Op::Move(v) => Some(v),
+            Op::CopySignals(_) => None,
+        })
+        .collect::<Vec<_>>();
+    if input.bl.min(a.len()) == 0 && !moves.is_empty() {
+        bail!("L_A and L_B must be positive.");
+    }
+    let mut in_a = vec![false; input.n];
+    for &v in a.iter() {
+        in_a[v] = true;
+    }
+    for &v in moves.iter() {
+        if !in_a[v] {
+            bail!("Vertex {} is not included in the array A.", v);
+        }
+    }
+
+    let b = vec![-1; input.bl];
+    let original = original_plan(output, moves.len());
+    let original = is_valid(input, a, &b, &moves, &original).then_some(original);
+    let plan = best_plan(input, a, &b, &moves, original).unwrap();
+
+    let mut builder = OutputBuilder::new();
+    for comment in output.initial_comments().iter() {
+        builder.initial_comment(comment);
+    }
+    builder.set_a(a.to_vec());
+
+    let mut plan = plan.into_iter().peekable();
+    let mut move_idx = 0;
+    for commented_op in output.commented_ops().iter() {
+        for comment in commented_op.comments.iter() {
+            builder.comment(comment);
+        }
+
+        if let Op::Move(v) = commented_op.op {
+            while let Some((_, cs)) = plan.next_if(|&(j, _)| j == move_idx) {
+                builder.copy_signals(cs.len, cs.p_a, cs.p_b);
+            }
+            builder.move_to(v);
+            move_idx += 1;
+        }
+    }
+    for comment in output.trailing_comments().iter() {
+        builder.comment(comment);
+    }
+
+    Ok(builder.build())
+}
+
+/// The number of copy operations needed to move along `path` from `state`, found in the same
+/// way as `greedy_reschedule`, or `None` if some vertex on it can never be made green.
+/// This is an upper bound on the minimum.
+pub fn copies_needed(input: &Input, state: &State, path: &[usize]) -> Option<usize> {
+    best_plan(input, &state.a, &state.b, path, None).map(|plan| plan.len())
+}
+
+// The plan with the fewest copies among the strategies
+fn best_plan(
+    input: &Input,
+    a: &[usize],
+    b: &[isize],
+    moves: &[usize],
+    original: Option<Plan>,
+) -> Option<Plan> {
+    [
+        greedy_plan(input, a, b, moves),
+        full_width_plan(input, a, b, moves),
+        original,
+    ]
+    .into_iter()
+    .flatten()
+    .min_by_key(|plan| plan.len())
+}
+
+// The copy operations of `output`, except those after the last move
+fn original_plan(output: &Output, move_cnt: usize) -> Plan {
+    let mut plan = vec![];
+    let mut move_idx = 0;
+    for commented_op in output.commented_ops().iter() {
+        match commented_op.op {
+            Op::CopySignals(cs) if move_idx < move_cnt => plan.push((move_idx, cs)),
+            Op::CopySignals(_) => {}
+            Op::Move(_) => move_idx += 1,
+        }
+    }
+    plan
+}
+
+// Whether every move is to a green vertex when `plan` is followed from B = `b`
+fn is_valid(input: &Input, a: &[usize], b: &[isize], moves: &[usize], plan: &Plan) -> bool {
+    let mut b = b.to_vec();
+    let mut plan = plan.iter().peekable();
+    for (j, &v) in moves.iter().enumerate() {
+        while let Some(&(_, cs)) = plan.next_if(|&&(k, _)| k == j) {
+            if cs.p_a + cs.len > a.len() || cs.p_b + cs.len > input.bl {
+                return false;
+            }
+            copy(a, &mut b, cs);
+        }
+        if !b.contains(&(v as isize)) {
+            return false;
+        }
+    }
+    true
+}
+
+fn copy(a: &[usize], b: &mut [isize], cs: CopySignals) {
+    for i in 0..cs.len {
+        b[cs.p_b + i] = a[cs.p_a + i] as isize;
+    }
+}
+
+/// Whenever the next move is to a red vertex, make the copy containing it after which the most
+/// moves can be made. The slots to overwrite are chosen so that the vertices in them are needed
+/// again as late as possible. Returns `None` if some vertex can never be made green.
+fn greedy_plan(input: &Input, a: &[usize], b: &[isize], moves: &[usize]) -> Option<Plan> {
+    let max_len = input.bl.min(a.len());
+    let mut pos = vec![vec![]; input.n];
+    for (i, &v) in a.iter().enumerate() {
+        pos[v].push(i);
+    }
+    let mut uses = vec![vec![]; input.n];
+    for (j, &v) in moves.iter().enumerate() {
+        uses[v].push(j);
+    }
+    // the index of the first move to v at or after j
+    let next_use = |v: usize, j: usize| {
+        let i = uses[v].partition_point(|&k| k < j);
+        uses[v].get(i).copied().unwrap_or(usize::MAX)
+    };
+
+    let mut b = b.to_vec();
+    let mut cnt = vec![0; input.n];
+    for &v in b.iter().filter(|&&v| v >= 0) {
+        cnt[v as usize] += 1;
+    }
+    // Add `diff` to the counts of the vertices in B[p_b..p_b + len]
+    let count_block = |cnt: &mut [usize], b: &[isize], p_b: usize, len: usize, diff: isize| {
+        for &u in b[p_b..p_b + len].iter().filter(|&&u| u >= 0) {
+            cnt[u as usize] = cnt[u as usize].wrapping_add_signed(diff);
+        }
+    };
+    // Whether vertex v lies in A[p_a..p_a + len]
+    let covers =
+        |p_a: usize, len: usize, v: usize| pos[v].iter().any(|&p| p_a <= p && p < p_a + len);
+
+    let mut plan = vec![];
+    let mut j = 0;
+    loop {
+        while j < moves.len() && cnt[moves[j]] > 0 {
+            j += 1;
+        }
+        if j == moves.len() {
+            return Some(plan);
+        }
+        let v = moves[j];
+        if pos[v].is_empty() || max_len == 0 {
+            return None;
+        }
+
+        // When the vertex in each slot is needed again if the slot is overwritten
+        let needed = b
+            .iter()
+            .map(|&u| {
+                if u < 0 || cnt[u as usize] > 1 {
+                    usize::MAX
+                } else {
+                    next_use(u as usize, j)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // (moves made after the copy, when the overwritten slots are needed, len), copy
+        let mut best: Option<((usize, usize, usize), CopySignals)> = None;
+        for len in 1..=max_len {
+            let mut p_b = 0;
+            let mut p_b_needed = 0;
+            for p in 0..=input.bl - len {
+                let min_needed = *needed[p..p + len].iter().min().unwrap();
+                if p == 0 || min_needed > p_b_needed {
+                    p_b = p;
+                    p_b_needed = min_needed;
+                }
+            }
+
+            // The moves from j to vertices not left in B out of the block, found lazily
+            count_block(&mut cnt, &b, p_b, len, -1);
+            let mut red = vec![];
+            let mut scanned = j;
+            for &p in pos[v].iter() {
+                for p_a in (p + 1).saturating_sub(len)..=p.min(a.len() - len) {
+                    let mut i = 0;
+                    let k = loop {
+                        if i == red.len() {
+                            while scanned < moves.len() && cnt[moves[scanned]] > 0 {
+                                scanned += 1;
+                            }
+                            if scanned == moves.len() {
+                                break scanned;
+                            }
+                            red.push(scanned);
+                            scanned += 1;
+                        }
+                        if !covers(p_a, len, moves[red[i]]) {
+                            break red[i];
+                        }
+                        i += 1;
+                    };
+
+                    let key = (k, p_b_needed, len);
+                    if best.is_none_or(|(best_key, _)| key > best_key) {
+                        best = Some((key, CopySignals { len, p_a, p_b }));
+                    }
+                }
+            }
+            count_block(&mut cnt, &b, p_b, len, 1);
+        }
+
+        let (_, cs) = best.unwrap();
+        count_block(&mut cnt, &b, cs.p_b, cs.len, -1);
+        copy(a, &mut b, cs);
+        count_block(&mut cnt, &b, cs.p_b, cs.len, 1);
+        plan.push((j, cs));
+    }
+}
+
+// Copies overwriting B[0..L_B] chosen by `greedy_windows`, made once the moves to the vertices
+// green in `b` are over
+pub(crate) fn full_width_plan(
+    input: &Input,
+    a: &[usize],
+    b: &[isize],
+    moves: &[usize],
+) -> Option<Plan> {
+    let green = moves
+        .iter()
+        .take_while(|&&v| b.contains(&(v as isize)))
+        .count();
+    let rest = &moves[green..];
+    let len = input.bl.min(a.len());
+    if rest.is_empty() {
+        return Some(vec![]);
+    }
+    let mut in_a = vec![false; input.n];
+    for &v in a.iter() {
+        in_a[v] = true;
+    }
+    if len == 0 || rest.iter().any(|&v| !in_a[v]) {
+        return None;
+    }
+    let plan = greedy_windows(input, a, len, rest)
+        .into_iter()
+        .map(|(start, p_a)| (green + start, CopySignals { len, p_a, p_b: 0 }))
+        .collect();
+    Some(plan)
+}
+
+/// Choose windows A[p_a..p_a + len] greedily so that each one keeps the following moves legal
+/// for as long as possible. Returns the index of the first move covered by each window and its
+/// p_a. Every vertex in `moves` must be in `a`, and `len` must be positive if `moves` is not empty.
+fn greedy_windows(input: &Input, a: &[usize], len: usize, moves: &[usize]) -> Vec<(usize, usize)> {
+    let mut pos = vec![vec![]; input.n];
+    for (i, &v) in a.iter().enumerate() {
+        pos[v].push(i);
+    }
+
+    // Whether vertex v lies in A[p_a..p_a + len]
+    let covers = |p_a: usize, v: usize| {
+        let i = pos[v].partition_point(|&p| p < p_a);
+        i < pos[v].len() && pos[v][i] < p_a + len
+    };
+
+    let mut windows = vec![];
+    // index of the first move not covered by the current window
+    let mut reach = 0;
+    while reach < moves.len() {
+        let start = reach;
+        let mut best_p_a = 0;
+        for &p in pos[moves[start]].iter() {
+            for p_a in (p + 1).saturating_sub(len)..=p.min(a.len() - len) {
+                let mut j = start;
+                while j < moves.len() && covers(p_a, moves[j]) {
+                    j += 1;
+                }
+                if j > reach {
+                    reach = j;
+                    best_p_a = p_a;
+                }
+            }
+        }
+        windows.push((start, best_p_a));
+    }
+    windows
+}
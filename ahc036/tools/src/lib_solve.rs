@@ -0,0 +1,59 @@
+use anyhow::{bail, Result};
+
+use crate::lib_reschedule::full_width_plan;
+use crate::{Graph, Input, Output, OutputBuilder};
+
+/// A simple solution which is valid for any connected input with N <= L_A and L_B > 0.
+///
+/// A is the DFS preorder from vertex 0 repeated up to L_A, the route follows a shortest path
+/// to each target, and each copy overwrites the whole B with the window of A which keeps the
+/// following moves legal for as long as possible.
+pub fn solve(input: &Input) -> Result<Output> {
+    if input.al < input.n {
+        bail!("L_A = {} is less than N = {}.", input.al, input.n);
+    }
+    let g = Graph::new(input);
+
+    let mut order = vec![];
+    let mut visited = vec![false; input.n];
+    let mut stack = vec![0];
+    while let Some(v) = stack.pop() {
+        if visited[v] {
+            continue;
+        }
+        visited[v] = true;
+        order.push(v);
+        // pushed in reverse so that smaller neighbors are visited first
+        stack.extend(g.neighbors(v).iter().rev().filter(|&&u| !visited[u]));
+    }
+    if order.len() < input.n {
+        bail!("The graph is disconnected.");
+    }
+    let a = order
+        .iter()
+        .cycle()
+        .take(input.al)
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut moves = vec![];
+    let mut cur = 0;
+    for &target in input.t.iter() {
+        moves.extend(g.shortest_path(cur, target).unwrap());
+        cur = target;
+    }
+    let Some(plan) = full_width_plan(input, &a, &vec![-1; input.bl], &moves) else {
+        bail!("L_B must be positive.");
+    };
+
+    let mut builder = OutputBuilder::new();
+    builder.set_a(a);
+    let mut plan = plan.into_iter().peekable();
+    for (j, &v) in moves.iter().enumerate() {
+        while let Some((_, cs)) = plan.next_if(|&(k, _)| k == j) {
+            builder.copy_signals(cs.len, cs.p_a, cs.p_b);
+        }
+        builder.move_to(v);
+    }
+    Ok(builder.build())
+}
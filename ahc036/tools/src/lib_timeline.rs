@@ -0,0 +1,178 @@
+use std::fmt;
+
+use crate::{CommentedOp, CopySignals, Graph, Input, Observer, Op, State, VisData};
+
+// A full copy of `State` is kept every CHECKPOINT_INTERVAL operations.
+// Any other turn is rebuilt by replaying at most CHECKPOINT_INTERVAL - 1 operations.
+const CHECKPOINT_INTERVAL: usize = 256;
+
+/// How far a replay got, meaningful even when `judge` rejected the output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The last turn whose operation was valid
+    pub last_valid_turn: usize,
+    pub target_idx: usize,
+    pub t_sz: usize,
+    pub copy_cnt: usize,
+    /// The score expected if the remaining targets cost as many copies per target as before
+    pub projected_score: Option<usize>,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Last valid turn = {}, Targets = {} / {}, Copies = {}",
+            self.last_valid_turn, self.target_idx, self.t_sz, self.copy_cnt
+        )?;
+        if let Some(projected_score) = self.projected_score {
+            write!(f, ", Projected score = {}", projected_score)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Timeline {
+    input: Input,
+    g: Graph,
+    initial_comments: Vec<String>,
+    checkpoints: Vec<State>,
+    commented_ops: Vec<CommentedOp>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Timeline {
+    pub fn new() -> Self {
+        Timeline {
+            input: Input::new(),
+            g: Graph::new(&Input::new()),
+            initial_comments: vec![],
+            checkpoints: vec![],
+            commented_ops: vec![],
+        }
+    }
+
+    fn start(input: &Input, initial_state: &State, initial_comments: &[String]) -> Self {
+        Timeline {
+            input: input.clone(),
+            g: Graph::new(input),
+            initial_comments: initial_comments.to_vec(),
+            checkpoints: vec![initial_state.clone()],
+            commented_ops: vec![],
+        }
+    }
+
+    // `state` must be the state right after `commented_op` was applied.
+    fn push(&mut self, commented_op: CommentedOp, state: &State) {
+        self.commented_ops.push(commented_op);
+        if self.commented_ops.len().is_multiple_of(CHECKPOINT_INTERVAL) {
+            self.checkpoints.push(state.clone());
+        }
+    }
+
+    /// Number of turns, including the initial one
+    pub fn len(&self) -> usize {
+        if self.checkpoints.is_empty() {
+            0
+        } else {
+            self.commented_ops.len() + 1
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Rebuild the data of the `t`-th turn
+    pub fn get(&self, t: usize) -> Option<VisData> {
+        if t >= self.len() {
+            return None;
+        }
+
+        let c = t / CHECKPOINT_INTERVAL;
+        let mut state = self.checkpoints[c].clone();
+        for commented_op in self.commented_ops[c * CHECKPOINT_INTERVAL..t].iter() {
+            let res = match commented_op.op {
+                Op::CopySignals(cs) => state.copy_signals(&self.input, cs),
+                Op::Move(v) => state.move_(&self.input, &self.g, v),
+            };
+            res.expect("recorded operations must be valid");
+        }
+
+        if t == 0 {
+            Some(VisData {
+                state,
+                initial_comments: Some(self.initial_comments.clone()),
+                commented_op: None,
+            })
+        } else {
+            Some(VisData {
+                state,
+                initial_comments: None,
+                commented_op: Some(self.commented_ops[t - 1].clone()),
+            })
+        }
+    }
+
+    pub fn last(&self) -> Option<VisData> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    pub fn progress(&self) -> Option<Progress> {
+        let state = self.last()?.state;
+        let t_sz = self.input.t_sz;
+        let projected_score =
+            (state.score * t_sz + state.target_idx / 2).checked_div(state.target_idx);
+
+        Some(Progress {
+            last_valid_turn: self.len() - 1,
+            target_idx: state.target_idx,
+            t_sz,
+            copy_cnt: state.score,
+            projected_score,
+        })
+    }
+}
+
+// Records every turn of a replay
+impl Observer for Timeline {
+    fn on_init(&mut self, input: &Input, state: &State, comments: &[String]) {
+        *self = Timeline::start(input, state, comments);
+    }
+
+    fn on_copy_signals(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        cs: CopySignals,
+        comments: &[String],
+    ) {
+        let commented_op = CommentedOp {
+            op: Op::CopySignals(cs),
+            comments: comments.to_vec(),
+        };
+        self.push(commented_op, state);
+    }
+
+    fn on_move(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        v: usize,
+        comments: &[String],
+    ) {
+        let commented_op = CommentedOp {
+            op: Op::Move(v),
+            comments: comments.to_vec(),
+        };
+        self.push(commented_op, state);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{CopySignals, Input, Observer, State};
+
+/// How the signals written by one copy operation were used
+#[derive(Clone, Debug)]
+pub struct CopyUsage {
+    pub op_idx: usize,
+    pub cs: CopySignals,
+    /// Vertices written to B[p_b..p_b + len]
+    pub values: Vec<usize>,
+    /// The number of moves relying on each written slot before it was overwritten
+    pub slot_moves: Vec<usize>,
+    /// Vertices which were red just before the operation
+    pub turned_green: Vec<usize>,
+}
+
+impl CopyUsage {
+    pub fn moves(&self) -> usize {
+        self.slot_moves.iter().sum()
+    }
+
+    pub fn wasted_slots(&self) -> usize {
+        self.slot_moves.iter().filter(|&&cnt| cnt == 0).count()
+    }
+
+    /// The number of vertices turned green by the operation and never moved to through it
+    pub fn unused_green(&self) -> usize {
+        self.turned_green
+            .iter()
+            .filter(|&&v| {
+                self.values
+                    .iter()
+                    .zip(self.slot_moves.iter())
+                    .all(|(&u, &cnt)| u != v || cnt == 0)
+            })
+            .count()
+    }
+}
+
+/// Attributes every move to the slot of B that made the destination green.
+/// When several slots hold the destination, the most recently written one is credited.
+#[derive(Clone, Debug, Default)]
+pub struct SignalUsage {
+    pub copies: Vec<CopyUsage>,
+    // the copy which wrote each slot of B, and the position in it
+    owner: Vec<Option<(usize, usize)>>,
+}
+
+impl SignalUsage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn moves(&self) -> usize {
+        self.copies.iter().map(|copy| copy.moves()).sum()
+    }
+
+    pub fn slots_written(&self) -> usize {
+        self.copies.iter().map(|copy| copy.cs.len).sum()
+    }
+
+    pub fn wasted_slots(&self) -> usize {
+        self.copies.iter().map(|copy| copy.wasted_slots()).sum()
+    }
+
+    pub fn turned_green(&self) -> usize {
+        self.copies.iter().map(|copy| copy.turned_green.len()).sum()
+    }
+
+    pub fn unused_green(&self) -> usize {
+        self.copies.iter().map(|copy| copy.unused_green()).sum()
+    }
+}
+
+impl Observer for SignalUsage {
+    fn on_init(&mut self, input: &Input, _state: &State, _comments: &[String]) {
+        *self = SignalUsage {
+            copies: vec![],
+            owner: vec![None; input.bl],
+        };
+    }
+
+    fn on_copy_signals(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        op_idx: usize,
+        cs: CopySignals,
+        _comments: &[String],
+    ) {
+        let range = cs.p_b..cs.p_b + cs.len;
+        let values = state.b()[range.clone()]
+            .iter()
+            .map(|&v| v as usize)
+            .collect::<Vec<_>>();
+
+        // A written vertex was red before iff no slot outside the range holds it
+        // and no slot in the range held it before the operation.
+        let mut turned_green = vec![];
+        for (i, &v) in values.iter().enumerate() {
+            if values[..i].contains(&v) {
+                continue;
+            }
+            let outside = state.traffic_light()[v] - values.iter().filter(|&&u| u == v).count();
+            let held_before = range.clone().any(|p| match self.owner[p] {
+                Some((c, j)) => self.copies[c].values[j] == v,
+                None => false,
+            });
+            if outside == 0 && !held_before {
+                turned_green.push(v);
+            }
+        }
+
+        let c = self.copies.len();
+        for (j, p) in range.enumerate() {
+            self.owner[p] = Some((c, j));
+        }
+        self.copies.push(CopyUsage {
+            op_idx,
+            cs,
+            slot_moves: vec![0; values.len()],
+            values,
+            turned_green,
+        });
+    }
+
+    fn on_move(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        v: usize,
+        _comments: &[String],
+    ) {
+        let mut latest: Option<(usize, usize)> = None;
+        for (p, &u) in state.b().iter().enumerate() {
+            if u == v as isize {
+                latest = latest.max(self.owner[p]);
+            }
+        }
+        if let Some((c, j)) = latest {
+            self.copies[c].slot_moves[j] += 1;
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::{
+    distance2, is_crossing, Input, UnionFind, BL_LB_SQRT, BL_UB_SQRT, VERTEX_MIN_DISTANCE_LB,
+};
+
+/// A guarantee of the generator which an input does not satisfy
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InputViolation {
+    /// L_A must satisfy N <= L_A <= 2N
+    AlOutOfRange {
+        al: usize,
+        n: usize,
+    },
+    /// L_B must satisfy 4 <= L_B <= 24
+    BlOutOfRange {
+        bl: usize,
+    },
+    SelfLoop {
+        edge_idx: usize,
+        v: usize,
+    },
+    DuplicateEdge {
+        edge_idx: usize,
+        first_idx: usize,
+    },
+    CrossingEdges {
+        edge_idx1: usize,
+        edge_idx2: usize,
+    },
+    Disconnected {
+        components: usize,
+    },
+    /// Two vertices are closer than the minimum vertex spacing
+    TooClose {
+        u: usize,
+        v: usize,
+    },
+    /// t_i equals t_{i-1}, where t_{-1} is the start vertex 0
+    RepeatedTarget {
+        target_idx: usize,
+        v: usize,
+    },
+}
+
+impl fmt::Display for InputViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            InputViolation::AlOutOfRange { al, n } => {
+                write!(f, "L_A = {} must be between {} and {}.", al, n, 2 * n)
+            }
+            InputViolation::BlOutOfRange { bl } => write!(
+                f,
+                "L_B = {} must be between {} and {}.",
+                bl,
+                BL_LB_SQRT.pow(2),
+                BL_UB_SQRT.pow(2) - 1
+            ),
+            InputViolation::SelfLoop { edge_idx, v } => {
+                write!(f, "{}-th edge is a self-loop on vertex {}.", edge_idx, v)
+            }
+            InputViolation::DuplicateEdge {
+                edge_idx,
+                first_idx,
+            } => write!(
+                f,
+                "{}-th edge is the same as {}-th edge.",
+                edge_idx, first_idx
+            ),
+            InputViolation::CrossingEdges {
+                edge_idx1,
+                edge_idx2,
+            } => write!(
+                f,
+                "{}-th edge and {}-th edge are crossing.",
+                edge_idx1, edge_idx2
+            ),
+            InputViolation::Disconnected { components } => {
+                write!(f, "The graph has {} connected components.", components)
+            }
+            InputViolation::TooClose { u, v } => write!(
+                f,
+                "Vertex {} and vertex {} are closer than {}.",
+                u, v, VERTEX_MIN_DISTANCE_LB
+            ),
+            InputViolation::RepeatedTarget { target_idx, v } => write!(
+                f,
+                "t_{} = {} is the same as the previous vertex.",
+                target_idx, v
+            ),
+        }
+    }
+}
+
+/// Check the structure of `input` against the guarantees of `construct_planar_graph` and `gen`.
+/// Every violation found is returned.
+pub fn validate_input(input: &Input) -> Vec<InputViolation> {
+    let mut violations = vec![];
+
+    if !(input.n..=2 * input.n).contains(&input.al) {
+        violations.push(InputViolation::AlOutOfRange {
+            al: input.al,
+            n: input.n,
+        });
+    }
+    if !(BL_LB_SQRT.pow(2)..BL_UB_SQRT.pow(2)).contains(&input.bl) {
+        violations.push(InputViolation::BlOutOfRange { bl: input.bl });
+    }
+
+    // self-loops and duplicate edges
+    let mut first_idx = HashMap::new();
+    let mut simple = vec![true; input.edges.len()];
+    for (i, &(u, v)) in input.edges.iter().enumerate() {
+        if u == v {
+            violations.push(InputViolation::SelfLoop { edge_idx: i, v });
+            simple[i] = false;
+        } else if let Some(&j) = first_idx.get(&(u.min(v), u.max(v))) {
+            violations.push(InputViolation::DuplicateEdge {
+                edge_idx: i,
+                first_idx: j,
+            });
+            simple[i] = false;
+        } else {
+            first_idx.insert((u.min(v), u.max(v)), i);
+        }
+    }
+
+    // planarity
+    let lines = input
+        .edges
+        .iter()
+        .map(|&(u, v)| (input.coordinates[u], input.coordinates[v]))
+        .collect::<Vec<_>>();
+    for i in 0..lines.len() {
+        for j in i + 1..lines.len() {
+            if simple[i] && simple[j] && is_crossing(&lines[i], &lines[j]) {
+                violations.push(InputViolation::CrossingEdges {
+                    edge_idx1: i,
+                    edge_idx2: j,
+                });
+            }
+        }
+    }
+
+    // connectivity
+    let mut uf = UnionFind::new(input.n);
+    for &(u, v) in input.edges.iter() {
+        uf.unite(u, v);
+    }
+    let components = (0..input.n).filter(|&v| uf.root(v) == v).count();
+    if components != 1 {
+        violations.push(InputViolation::Disconnected { components });
+    }
+
+    // vertex spacing
+    for u in 0..input.n {
+        for v in u + 1..input.n {
+            if distance2(input.coordinates[u], input.coordinates[v]) < VERTEX_MIN_DISTANCE_LB.pow(2)
+            {
+                violations.push(InputViolation::TooClose { u, v });
+            }
+        }
+    }
+
+    // targets
+    let mut prev = 0;
+    for (i, &v) in input.t.iter().enumerate() {
+        if v == prev {
+            violations.push(InputViolation::RepeatedTarget { target_idx: i, v });
+        }
+        prev = v;
+    }
+
+    violations
+}
@@ -1,7 +1,7 @@
-use svg::node::element::{Circle, Line, Polygon, Rectangle, Title, SVG};
+use svg::node::element::{Circle, Line, Polygon, Rectangle, Text as TextElement, Title, SVG};
 use svg::node::Text;
 
-use crate::{Input, VisData};
+use crate::{copies_needed, parse_directive, Directive, Graph, HeatmapMode, Input, Op, VisData};
 
 const W: f64 = 800.0;
 const H: f64 = 800.0;
@@ -9,23 +9,50 @@ const PADDING: f64 = 10.0;
 const COORD_MAX: f64 = 1000.0;
 const VERTEX_SZ: f64 = 5.0;
 const RATIO: f64 = H / COORD_MAX;
+// The arrays are drawn under the graph, wrapped every ARRAY_COLS cells
+const CELL_SZ: f64 = 20.0;
+const ARRAY_COLS: usize = 40;
+// Space for the label above each array
+const LABEL_H: f64 = 24.0;
 
-pub fn new_svg() -> SVG {
+fn array_rows(len: usize) -> usize {
+    len.div_ceil(ARRAY_COLS).max(1)
+}
+
+fn b_top() -> f64 {
+    H + PADDING + LABEL_H
+}
+
+fn a_top(input: &Input) -> f64 {
+    b_top() + CELL_SZ * array_rows(input.bl) as f64 + LABEL_H
+}
+
+/// Height of the drawing including the arrays
+pub fn svg_height(input: &Input) -> f64 {
+    a_top(input) + CELL_SZ * array_rows(input.al) as f64
+}
+
+pub fn new_svg(height: f64) -> SVG {
     let mut doc = svg::Document::new()
         .set("id", "vis")
         .set(
             "viewBox",
-            (-PADDING, -PADDING, W + 2.0 * PADDING, H + 2.0 * PADDING),
+            (
+                -PADDING,
+                -PADDING,
+                W + 2.0 * PADDING,
+                height + 2.0 * PADDING,
+            ),
         )
         .set("width", W + 2.0 * PADDING)
-        .set("height", H + 2.0 * PADDING);
+        .set("height", height + 2.0 * PADDING);
 
     doc = doc.add(
         Rectangle::new()
             .set("x", -PADDING)
             .set("y", -PADDING)
             .set("width", W + 2.0 * PADDING)
-            .set("height", H + 2.0 * PADDING)
+            .set("height", height + 2.0 * PADDING)
             .set("fill", "white")
             .set("stroke-width", "0.0"),
     );
@@ -33,7 +60,7 @@ pub fn new_svg() -> SVG {
     doc
 }
 
-pub fn draw_graph(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
+pub fn draw_graph(input: &Input, mut doc: SVG, heatmap: Option<&(HeatmapMode, Vec<usize>)>) -> SVG {
     // edge
     for &(u, v) in input.edges.iter() {
         let (x1, y1) = input.coordinates[u];
@@ -42,18 +69,48 @@ pub fn draw_graph(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
     }
 
     // vertex
+    if let Some((mode, values)) = heatmap {
+        let max = values.iter().max().copied().unwrap_or(0).max(1);
+        for (i, &(x, y)) in input.coordinates.iter().enumerate() {
+            let col = color(values[i] as f64 / max as f64);
+            doc = draw_circle(doc, x, y, VERTEX_SZ * 1.2, &col, 1.0, None, 0.0, RATIO);
+        }
+        doc = doc.add(
+            TextElement::new()
+                .set("x", W / 2.0)
+                .set("y", b_top() - 6.0)
+                .set("font-size", 14)
+                .set("text-anchor", "middle")
+                .add(Text::new(format!("{}: 0 - {}", mode, max))),
+        );
+        return doc;
+    }
+
+    // All vertices are red until `draw_green_vertices` draws over them
+    for &(x, y) in input.coordinates.iter() {
+        doc = draw_circle(doc, x, y, VERTEX_SZ, "orangered", 1.0, None, 0.0, RATIO)
+    }
+
+    doc
+}
+
+pub fn draw_green_vertices(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
     for (i, &(x, y)) in input.coordinates.iter().enumerate() {
-        if vis_data.state.traffic_light[i] == 0 {
-            let col = "orangered";
-            let sz = 1.0;
-            doc = draw_circle(doc, x, y, VERTEX_SZ * sz, col, 1.0, None, 0.0, RATIO)
-        } else {
-            let col = "mediumseagreen";
+        if vis_data.state.traffic_light[i] > 0 {
             let sz = 1.5;
-            doc = draw_circle(doc, x, y, VERTEX_SZ * sz, col, 1.0, None, 0.0, RATIO)
+            doc = draw_circle(
+                doc,
+                x,
+                y,
+                VERTEX_SZ * sz,
+                "mediumseagreen",
+                1.0,
+                None,
+                0.0,
+                RATIO,
+            )
         }
     }
-
     doc
 }
 
@@ -110,6 +167,56 @@ pub fn draw_visited_vertices(vis_data: &VisData, input: &Input, mut doc: SVG, k:
     doc
 }
 
+// Draw a shortest path from the current vertex to the current target with the signals on it,
+// and the number of copies still needed to move along it as estimated by `copies_needed`
+pub fn draw_shortest_path(vis_data: &VisData, input: &Input, g: &Graph, mut doc: SVG) -> SVG {
+    let state = &vis_data.state;
+    if state.target_idx >= input.t_sz {
+        return doc;
+    }
+    let target = input.t[state.target_idx];
+    let Some(path) = g.shortest_path(state.cur_v, target) else {
+        return doc;
+    };
+
+    let col = "darkorange";
+    let mut prev = state.cur_v;
+    for &v in path.iter() {
+        let (x1, y1) = input.coordinates[prev];
+        let (x2, y2) = input.coordinates[v];
+        doc = draw_line(doc, x1, y1, x2, y2, Some(col), 3.0, RATIO);
+        prev = v;
+    }
+    for &v in path.iter() {
+        let (x, y) = input.coordinates[v];
+        let fill = if state.traffic_light[v] > 0 {
+            "mediumseagreen"
+        } else {
+            "orangered"
+        };
+        doc = draw_circle(doc, x, y, VERTEX_SZ * 1.5, fill, 1.0, Some(col), 1.5, RATIO);
+    }
+
+    let copies = match copies_needed(input, state, &path) {
+        Some(count) => format!("{} copies needed (greedy estimate)", count),
+        None => "a vertex is not in A".to_string(),
+    };
+    doc.add(
+        TextElement::new()
+            .set("x", W)
+            .set("y", b_top() - 6.0)
+            .set("font-size", 14)
+            .set("text-anchor", "end")
+            .add(Text::new(format!(
+                "Path to t[{}] = {}: {} moves, {}",
+                state.target_idx,
+                target,
+                path.len(),
+                copies
+            ))),
+    )
+}
+
 pub fn draw_tooltips(input: &Input, mut doc: SVG) -> SVG {
     for i in 0..input.n {
         let (x, y) = input.coordinates[i];
@@ -130,6 +237,265 @@ pub fn draw_tooltips(input: &Input, mut doc: SVG) -> SVG {
     doc
 }
 
+// Draw the labels of the arrays and the cells of A, which stays the same over a replay
+pub fn draw_array_cells(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
+    doc = draw_label(doc, "B", b_top() - 6.0);
+    doc = draw_label(doc, "A", a_top(input) - 6.0);
+    for (i, &v) in vis_data.state.a.iter().enumerate() {
+        doc = draw_cell(
+            doc,
+            cell_pos(a_top(input), i),
+            "white",
+            false,
+            &v.to_string(),
+            format!("A[{}] = {}", i, v),
+        );
+    }
+    doc
+}
+
+// Draw the signals over the cells of `draw_array_cells`.
+// The window of A copied by the current operation and the slots of B written by it are outlined,
+// cells of A holding a vertex in B are filled, and each slot of B is linked to its vertex.
+pub fn draw_arrays(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
+    let state = &vis_data.state;
+    let (copied_a, copied_b) = match vis_data.commented_op.as_ref().map(|c| &c.op) {
+        Some(Op::CopySignals(cs)) => (cs.p_a..cs.p_a + cs.len, cs.p_b..cs.p_b + cs.len),
+        _ => (0..0, 0..0),
+    };
+
+    for (j, &v) in state.b.iter().enumerate() {
+        let (x, y) = cell_pos(b_top(), j);
+        if v >= 0 {
+            let (vx, vy) = input.coordinates[v as usize];
+            doc = doc.add(
+                Line::new()
+                    .set("x1", x + CELL_SZ / 2.0)
+                    .set("y1", y)
+                    .set("x2", vx as f64 * RATIO)
+                    .set("y2", vy as f64 * RATIO)
+                    .set("stroke", "mediumseagreen")
+                    .set("stroke-width", 0.6)
+                    .set("stroke-opacity", 0.5),
+            );
+        }
+        let fill = if v >= 0 { "mediumseagreen" } else { "white" };
+        let label = if v >= 0 {
+            v.to_string()
+        } else {
+            "-".to_string()
+        };
+        doc = draw_cell(
+            doc,
+            (x, y),
+            fill,
+            copied_b.contains(&j),
+            &label,
+            format!("B[{}] = {}", j, label),
+        );
+    }
+
+    for (i, &v) in state.a.iter().enumerate() {
+        let (x, y) = cell_pos(a_top(input), i);
+        if state.traffic_light[v] > 0 {
+            doc = doc.add(
+                Rectangle::new()
+                    .set("x", x)
+                    .set("y", y)
+                    .set("width", CELL_SZ)
+                    .set("height", CELL_SZ)
+                    .set("fill", "mediumseagreen")
+                    .set("fill-opacity", 0.4)
+                    .set("pointer-events", "none"),
+            );
+        }
+        if copied_a.contains(&i) {
+            doc = draw_cell_outline(doc, (x, y), "blue");
+        }
+    }
+
+    doc
+}
+
+// Draw the directives in the comments attached to the frame.
+// Texts and malformed directives are listed at the top left of the graph.
+pub fn draw_directives(vis_data: &VisData, input: &Input, mut doc: SVG) -> SVG {
+    let comments = match (&vis_data.commented_op, &vis_data.initial_comments) {
+        (Some(commented_op), _) => &commented_op.comments[..],
+        (None, Some(comments)) => &comments[..],
+        (None, None) => &[],
+    };
+
+    let mut texts = vec![];
+    for comment in comments.iter() {
+        let directive = match parse_directive(comment, input) {
+            Some(Ok(directive)) => directive,
+            Some(Err(err)) => {
+                texts.push((format!("{}: {}", comment, err), "red"));
+                continue;
+            }
+            None => continue,
+        };
+
+        match directive {
+            Directive::MarkVertex { v, color } => {
+                let (x, y) = input.coordinates[v];
+                doc = draw_circle(
+                    doc,
+                    x,
+                    y,
+                    VERTEX_SZ * 2.2,
+                    "none",
+                    1.0,
+                    Some(&color),
+                    2.5,
+                    RATIO,
+                );
+            }
+            Directive::MarkA { p, len, color } => {
+                for i in p..p + len {
+                    doc = draw_cell_outline(doc, cell_pos(a_top(input), i), &color);
+                }
+            }
+            Directive::MarkB { p, len, color } => {
+                for j in p..p + len {
+                    doc = draw_cell_outline(doc, cell_pos(b_top(), j), &color);
+                }
+            }
+            Directive::Path { vertices, color } => {
+                for w in vertices.windows(2) {
+                    let (x1, y1) = input.coordinates[w[0]];
+                    let (x2, y2) = input.coordinates[w[1]];
+                    doc = draw_line(doc, x1, y1, x2, y2, Some(&color), 2.5, RATIO);
+                }
+                for &v in vertices.iter() {
+                    let (x, y) = input.coordinates[v];
+                    doc = draw_circle(doc, x, y, VERTEX_SZ * 0.8, &color, 1.0, None, 0.0, RATIO);
+                }
+            }
+            Directive::Text(text) => texts.push((text, "black")),
+        }
+    }
+
+    for (i, (text, color)) in texts.into_iter().enumerate() {
+        doc = doc.add(
+            TextElement::new()
+                .set("x", 0.0)
+                .set("y", 14.0 + 16.0 * i as f64)
+                .set("font-size", 14)
+                .set("fill", color)
+                .set("stroke", "white")
+                .set("stroke-width", 3)
+                .set("paint-order", "stroke")
+                .add(Text::new(escape(&text))),
+        );
+    }
+
+    doc
+}
+
+// Text nodes are written as they are, so characters of markup in comments must be escaped
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn draw_cell_outline(doc: SVG, (x, y): (f64, f64), color: &str) -> SVG {
+    doc.add(
+        Rectangle::new()
+            .set("x", x)
+            .set("y", y)
+            .set("width", CELL_SZ)
+            .set("height", CELL_SZ)
+            .set("fill", "none")
+            .set("stroke", color)
+            .set("stroke-width", 2.5)
+            .set("pointer-events", "none"),
+    )
+}
+
+fn cell_pos(top: f64, i: usize) -> (f64, f64) {
+    (
+        (i % ARRAY_COLS) as f64 * CELL_SZ,
+        top + (i / ARRAY_COLS) as f64 * CELL_SZ,
+    )
+}
+
+fn draw_label(doc: SVG, label: &str, y: f64) -> SVG {
+    doc.add(
+        TextElement::new()
+            .set("x", 0.0)
+            .set("y", y)
+            .set("font-size", 14)
+            .add(Text::new(label)),
+    )
+}
+
+fn draw_cell(
+    doc: SVG,
+    (x, y): (f64, f64),
+    fill: &str,
+    highlighted: bool,
+    label: &str,
+    title: String,
+) -> SVG {
+    let (stroke, stroke_width) = if highlighted {
+        ("blue", 2.0)
+    } else {
+        ("lightgray", 0.8)
+    };
+    doc.add(
+        Rectangle::new()
+            .set("x", x)
+            .set("y", y)
+            .set("width", CELL_SZ)
+            .set("height", CELL_SZ)
+            .set("fill", fill)
+            .set("fill-opacity", 0.4)
+            .set("stroke", stroke)
+            .set("stroke-width", stroke_width)
+            .add(Title::new().add(Text::new(title))),
+    )
+    .add(
+        TextElement::new()
+            .set("x", x + CELL_SZ / 2.0)
+            .set("y", y + CELL_SZ / 2.0)
+            .set("font-size", 8)
+            .set("text-anchor", "middle")
+            .set("dominant-baseline", "central")
+            .set("pointer-events", "none")
+            .add(Text::new(label)),
+    )
+}
+
+// Blue for 0.0, green for 0.5 and red for 1.0
+fn color(val: f64) -> String {
+    let val = val.clamp(0.0, 1.0);
+    let (r, g, b) = if val < 0.5 {
+        let x = val * 2.0;
+        (
+            30. * (1.0 - x) + 144. * x,
+            144. * (1.0 - x) + 255. * x,
+            255. * (1.0 - x) + 30. * x,
+        )
+    } else {
+        let x = val * 2.0 - 1.0;
+        (
+            144. * (1.0 - x) + 255. * x,
+            255. * (1.0 - x) + 30. * x,
+            30. * (1.0 - x) + 70. * x,
+        )
+    };
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        r.round() as i32,
+        g.round() as i32,
+        b.round() as i32
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
 fn draw_rectangle(
     doc: SVG,
     x: i64,
@@ -161,6 +527,7 @@ fn draw_rectangle(
     doc.add(rect)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_line(
     doc: SVG,
     x1: i64,
@@ -184,6 +551,7 @@ fn draw_line(
     doc.add(line)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw_circle(
     doc: SVG,
     cx: i64,
@@ -0,0 +1,140 @@
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use std::collections::{HashMap, VecDeque};
+use tools::{copy_lower_bound, Graph, Input, InputBuilder};
+
+// Every array of length L_A over the vertices
+fn all_arrays(n: usize, al: usize) -> Vec<Vec<usize>> {
+    let mut arrays = vec![vec![]];
+    for _ in 0..al {
+        arrays = arrays
+            .into_iter()
+            .flat_map(|a| {
+                (0..n).map(move |v| {
+                    let mut a = a.clone();
+                    a.push(v);
+                    a
+                })
+            })
+            .collect();
+    }
+    arrays
+}
+
+// The minimum number of signal operations over every output, by a 0-1 BFS over
+// (current vertex, index of the next target, B) for each A
+fn optimum(input: &Input) -> Option<usize> {
+    let g = Graph::new(input);
+    let (al, bl) = (input.al(), input.bl());
+    let mut best: Option<usize> = None;
+    for a in all_arrays(input.n(), al) {
+        let start = (0, 0, vec![-1isize; bl]);
+        let mut dist = HashMap::from([(start.clone(), 0)]);
+        let mut que = VecDeque::from([(0, start)]);
+        while let Some((d, (cur, idx, b))) = que.pop_front() {
+            if best.is_some_and(|best| best <= d) {
+                break;
+            }
+            if dist[&(cur, idx, b.clone())] < d {
+                continue;
+            }
+            if idx == input.t_sz() {
+                best = Some(d);
+                break;
+            }
+
+            for &v in g.neighbors(cur) {
+                if b.contains(&(v as isize)) {
+                    let next_idx = if input.t()[idx] == v { idx + 1 } else { idx };
+                    let next = (v, next_idx, b.clone());
+                    if dist.get(&next).is_none_or(|&nd| nd > d) {
+                        dist.insert(next.clone(), d);
+                        que.push_front((d, next));
+                    }
+                }
+            }
+            for len in 1..=al.min(bl) {
+                for p_a in 0..=al - len {
+                    for p_b in 0..=bl - len {
+                        let mut next_b = b.clone();
+                        for i in 0..len {
+                            next_b[p_b + i] = a[p_a + i] as isize;
+                        }
+                        let next = (cur, idx, next_b);
+                        if dist.get(&next).is_none_or(|&nd| nd > d + 1) {
+                            dist.insert(next.clone(), d + 1);
+                            que.push_back((d + 1, next));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+// A path 0 - 1 - ... - (n - 1)
+fn path(n: usize, bl: usize, t: &[usize]) -> Input {
+    let mut builder = InputBuilder::new();
+    for i in 0..n {
+        builder.vertex(i as i64 * 50, 0);
+    }
+    for i in 1..n {
+        builder.edge(i - 1, i);
+    }
+    for &v in t.iter() {
+        builder.target(v);
+    }
+    builder.set_bl(bl).build_unchecked().unwrap()
+}
+
+#[test]
+fn bound_is_tight_on_paths() {
+    // A walk to the end of the path makes L_B moves per copy
+    for (n, bl, t, expected) in [
+        (4, 3, vec![3], 1),
+        (5, 2, vec![4], 2),
+        (5, 1, vec![4], 4),
+        // Going back and forth over three vertices needs all of them in B
+        (4, 2, vec![2, 0, 2], 3),
+        (4, 3, vec![2, 0, 2], 1),
+        // Distinct targets beyond L_B can not share a phase
+        (5, 3, vec![1, 2, 3, 4], 2),
+    ] {
+        let input = path(n, bl, &t);
+        assert_eq!(optimum(&input), Some(expected), "{} {} {:?}", n, bl, t);
+        assert_eq!(copy_lower_bound(&input), expected, "{} {} {:?}", n, bl, t);
+    }
+}
+
+#[test]
+fn bound_never_exceeds_the_optimum() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for _ in 0..30 {
+        let n = rng.gen_range(3..=4);
+        let mut builder = InputBuilder::new();
+        for i in 0..n {
+            builder.vertex(i as i64 * 50, rng.gen_range(0..1000));
+        }
+        // a random tree and an extra edge
+        for v in 1..n {
+            builder.edge(rng.gen_range(0..v), v);
+        }
+        builder.edge(0, n - 1);
+        let mut prev = 0;
+        for _ in 0..rng.gen_range(1..=4) {
+            let v = (prev + rng.gen_range(1..n)) % n;
+            builder.target(v);
+            prev = v;
+        }
+        let input = builder
+            .set_al(n)
+            .set_bl(rng.gen_range(1..=3))
+            .build_unchecked()
+            .unwrap();
+
+        let opt = optimum(&input).unwrap();
+        let bound = copy_lower_bound(&input);
+        assert!(bound <= opt, "{} > {}\n{}", bound, opt, input);
+    }
+}
@@ -0,0 +1,62 @@
+// Fixtures shared by the integration tests. Each test file uses only some of them.
+#![allow(dead_code)]
+
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::ChaCha20Rng;
+use tools::{CopySignals, Graph, Input, OutputBuilder};
+
+// Inputs of the generator checked in under in/
+pub const SAMPLE_FILES: [&str; 3] = ["in/0000.txt", "in/0001.txt", "in/0002.txt"];
+
+// The longest copy accepted by the parser which fits in both arrays
+pub fn max_len(input: &Input) -> usize {
+    input.al().min(input.bl()).min(input.n() - 1)
+}
+
+pub fn random_copy(rng: &mut ChaCha20Rng, input: &Input) -> CopySignals {
+    let len = rng.gen_range(1..=max_len(input));
+    CopySignals {
+        len,
+        p_a: rng.gen_range(0..=input.al() - len),
+        p_b: rng.gen_range(0..=input.bl() - len),
+    }
+}
+
+// A random output visiting every target, with `copy_cnt` copy operations.
+// Besides the copies needed to turn the next vertex green, random ones are mixed in.
+pub fn random_valid_output(rng: &mut ChaCha20Rng, input: &Input) -> (OutputBuilder, usize) {
+    let g = Graph::new(input);
+    let mut a = (0..input.n()).collect::<Vec<_>>();
+    while a.len() < input.al() {
+        a.push(rng.gen_range(0..input.n()));
+    }
+    a.shuffle(rng);
+
+    let mut builder = OutputBuilder::new();
+    builder.set_a(a.clone());
+    let mut b = vec![usize::MAX; input.bl()];
+    let mut copy_cnt = 0;
+    let mut copy = |builder: &mut OutputBuilder, b: &mut Vec<usize>, cs: CopySignals| {
+        builder.copy_signals(cs.len, cs.p_a, cs.p_b);
+        b[cs.p_b..cs.p_b + cs.len].copy_from_slice(&a[cs.p_a..cs.p_a + cs.len]);
+        copy_cnt += 1;
+    };
+
+    let mut cur = 0;
+    for &t in input.t() {
+        for v in g.shortest_path(cur, t).unwrap() {
+            while rng.gen_bool(0.2) {
+                copy(&mut builder, &mut b, random_copy(rng, input));
+            }
+            if !b.contains(&v) {
+                let p_a = a.iter().position(|&x| x == v).unwrap();
+                let len = rng.gen_range(1..=max_len(input).min(input.al() - p_a));
+                let p_b = rng.gen_range(0..=input.bl() - len);
+                copy(&mut builder, &mut b, CopySignals { len, p_a, p_b });
+            }
+            builder.move_to(v);
+        }
+        cur = t;
+    }
+    (builder, copy_cnt)
+}
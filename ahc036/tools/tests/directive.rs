@@ -0,0 +1,201 @@
+use tools::{
+    draw_svg, judge, parse_directive, parse_input_dsl, Directive, Input, OutputBuilder, Timeline,
+};
+
+// A zigzag of five vertices with LA = 5 and LB = 4
+const LINE: &str = "
+la 5
+lb 4
+v 0 0
+v 100 100
+v 200 0
+v 300 100
+v 400 0
+e 0 1 2 3 4
+t 1
+";
+
+fn line() -> Input {
+    parse_input_dsl(LINE).unwrap().build().unwrap()
+}
+
+fn parse(comment: &str) -> Result<Directive, String> {
+    parse_directive(comment, &line()).expect("not a directive")
+}
+
+#[test]
+fn comments_without_at_are_not_directives() {
+    let input = line();
+    for comment in ["", "mark v 0", " @mark v 0", "score 10"] {
+        assert_eq!(parse_directive(comment, &input), None, "{}", comment);
+    }
+}
+
+#[test]
+fn trailing_token_other_than_a_number_is_the_color() {
+    for (comment, expected) in [
+        (
+            "@mark v 3",
+            Directive::MarkVertex {
+                v: 3,
+                color: "magenta".to_string(),
+            },
+        ),
+        (
+            "@mark v 3 red",
+            Directive::MarkVertex {
+                v: 3,
+                color: "red".to_string(),
+            },
+        ),
+        (
+            "@mark b 1 #00ff00",
+            Directive::MarkB {
+                p: 1,
+                len: 1,
+                color: "#00ff00".to_string(),
+            },
+        ),
+        (
+            "@path 0 1 2",
+            Directive::Path {
+                vertices: vec![0, 1, 2],
+                color: "purple".to_string(),
+            },
+        ),
+        (
+            "@path  4  3\tblue ",
+            Directive::Path {
+                vertices: vec![4, 3],
+                color: "blue".to_string(),
+            },
+        ),
+    ] {
+        assert_eq!(parse(comment), Ok(expected), "{}", comment);
+    }
+}
+
+#[test]
+fn ranges_fit_in_the_arrays() {
+    for (comment, expected) in [
+        (
+            "@mark a 4",
+            Directive::MarkA {
+                p: 4,
+                len: 1,
+                color: "magenta".to_string(),
+            },
+        ),
+        (
+            "@mark a 0 5",
+            Directive::MarkA {
+                p: 0,
+                len: 5,
+                color: "magenta".to_string(),
+            },
+        ),
+        (
+            "@mark b 1 3 red",
+            Directive::MarkB {
+                p: 1,
+                len: 3,
+                color: "red".to_string(),
+            },
+        ),
+        (
+            "@mark b 3 0",
+            Directive::MarkB {
+                p: 3,
+                len: 0,
+                color: "magenta".to_string(),
+            },
+        ),
+    ] {
+        assert_eq!(parse(comment), Ok(expected), "{}", comment);
+    }
+}
+
+#[test]
+fn text_may_be_quoted() {
+    for (comment, expected) in [
+        ("@text", ""),
+        ("@text  hello world ", "hello world"),
+        ("@text \"  padded \"", "  padded "),
+        ("@text \"@mark v 0\"", "@mark v 0"),
+        ("@text \"unbalanced", "\"unbalanced"),
+        ("@text a < b & c", "a < b & c"),
+    ] {
+        assert_eq!(
+            parse(comment),
+            Ok(Directive::Text(expected.to_string())),
+            "{}",
+            comment
+        );
+    }
+}
+
+#[test]
+fn malformed_directives_are_explained() {
+    for (comment, expected) in [
+        ("@", "Unknown directive: @"),
+        ("@marks v 0", "Unknown directive: @marks"),
+        ("@Mark v 0", "Unknown directive: @Mark"),
+        ("@mark", "@mark needs v, a or b"),
+        ("@mark c 0", "@mark needs v, a or b: c"),
+        ("@mark v 0 red!", "Invalid color: red!"),
+        ("@mark v", "Usage: @mark v ID [COLOR]"),
+        ("@mark v red", "Usage: @mark v ID [COLOR]"),
+        ("@mark v 0 1", "Usage: @mark v ID [COLOR]"),
+        ("@mark v 5", "ID = 5 must be less than 5"),
+        ("@mark v -1 red", "ID must be a number: -1"),
+        ("@mark a", "Usage: @mark a POS [LEN] [COLOR]"),
+        ("@mark b 0 1 2", "Usage: @mark b POS [LEN] [COLOR]"),
+        ("@mark a 5", "POS = 5 must be less than 5"),
+        ("@mark b 4", "POS = 4 must be less than 4"),
+        ("@mark b 0 5", "LEN = 5 must be less than 5"),
+        ("@mark a 1x 2", "POS must be a number: 1x"),
+        ("@mark a 1 2x red", "LEN must be a number: 2x"),
+        ("@mark a 1 5", "POS + LEN = 6 must be at most 5"),
+        ("@mark b 3 2 red", "POS + LEN = 5 must be at most 4"),
+        ("@path", "Usage: @path V_1 V_2 ... [COLOR]"),
+        ("@path red", "Usage: @path V_1 V_2 ... [COLOR]"),
+        ("@path 0 5", "V = 5 must be less than 5"),
+        ("@path 0 x 1", "V must be a number: x"),
+        ("@path 0 1 bad-color", "Invalid color: bad-color"),
+    ] {
+        assert_eq!(parse(comment), Err(expected.to_string()), "{}", comment);
+    }
+}
+
+#[test]
+fn texts_are_escaped_in_the_svg() {
+    let input = line();
+    let mut builder = OutputBuilder::new();
+    builder
+        .set_a(vec![0, 1, 2, 3, 4])
+        .comment("@text a < b && c > d")
+        .comment("@path 0 <1>")
+        .copy_signals(1, 1, 0)
+        .move_to(1);
+
+    let mut timeline = Timeline::new();
+    let mut input_res = Input::new();
+    judge(
+        &input.to_string(),
+        &builder.build().to_string(),
+        &mut timeline,
+        &mut input_res,
+        5,
+    )
+    .unwrap();
+    // Turn 1 is the copy carrying the comments
+    let svg = draw_svg(&timeline.get(1).unwrap(), &input, 5).to_string();
+    assert!(svg.contains("\na &lt; b &amp;&amp; c &gt; d\n"), "{}", svg);
+    assert!(
+        svg.contains("\n@path 0 &lt;1&gt;: Invalid color: &lt;1&gt;\n"),
+        "{}",
+        svg
+    );
+    assert!(!svg.contains("a < b"));
+    assert!(!svg.contains("<1>"));
+}
@@ -0,0 +1,129 @@
+use tools::{export_input, gen_with, input_from_json, GenMode, GenParams, GraphFormat};
+
+const SEEDS: u64 = 3;
+
+// The smallest input, with the given text in place of the "LA" member
+fn small_json(la: &str) -> String {
+    format!(
+        "{{{}, \"LB\": 4, \"edges\": [[0, 1]], \"targets\": [1], \"coordinates\": [[0, 0], [100, 0]]}}",
+        la
+    )
+}
+
+#[test]
+fn json_round_trips() {
+    for mode in GenMode::ALL {
+        for seed in 0..SEEDS {
+            let mut params = GenParams::default();
+            mode.apply(&mut params);
+            let (input, _) = gen_with(seed, &params).unwrap();
+            let json = export_input(&input, GraphFormat::Json);
+            let imported = input_from_json(&json).unwrap();
+            assert_eq!(imported.to_string(), input.to_string(), "{} {}", mode, seed);
+        }
+    }
+}
+
+#[test]
+fn string_escapes_are_decoded() {
+    let input = input_from_json(&small_json("\"L\\u0041\": 2")).unwrap();
+    assert_eq!((input.n(), input.al(), input.bl()), (2, 2, 4));
+
+    // Keys with a surrogate pair and other escapes are accepted and ignored
+    let json = small_json("\"\\ud83d\\ude00 \\\" \\n \\/\": null, \"LA\": 2");
+    assert_eq!(input_from_json(&json).unwrap().al(), 2);
+}
+
+#[test]
+fn malformed_json_is_rejected() {
+    for (json, expected) in [
+        (
+            "".to_string(),
+            "EOF while parsing a value at line 1 column 0",
+        ),
+        ("{\"LA\": 2".to_string(), "EOF while parsing an object"),
+        ("{\"LA".to_string(), "EOF while parsing a string"),
+        (
+            "{\"LA\": tru}".to_string(),
+            "expected ident at line 1 column 11",
+        ),
+        (
+            format!("{} x", small_json("\"LA\": 2")),
+            "trailing characters",
+        ),
+        (
+            small_json("\"\\ud83d\": 0, \"LA\": 2"),
+            "unexpected end of hex escape",
+        ),
+        (
+            small_json("\"\\ud83d\\u0041\": 0, \"LA\": 2"),
+            "lone leading surrogate in hex escape",
+        ),
+        (
+            small_json("\"\\ude00\": 0, \"LA\": 2"),
+            "lone leading surrogate in hex escape",
+        ),
+        (small_json("\"\\u12g4\": 0, \"LA\": 2"), "invalid escape"),
+        (small_json("\"\\u+123\": 0, \"LA\": 2"), "invalid escape"),
+        (small_json("\"la\": 2"), "missing field `LA`"),
+        (small_json("\"LB\": 4"), "duplicate field `LB`"),
+        (
+            small_json("\"LA\": -1"),
+            "invalid value: integer `-1`, expected usize",
+        ),
+        (
+            small_json("\"LA\": 1.5"),
+            "invalid type: floating point `1.5`, expected usize",
+        ),
+        (
+            small_json("\"LA\": [2]"),
+            "invalid type: sequence, expected usize",
+        ),
+        (
+            small_json("\"LA\": 2, \"N\": 3"),
+            "N = 3 does not match the data (2)",
+        ),
+        (
+            small_json("\"LA\": 2").replace("[[0, 1]]", "[[0, 2]]"),
+            "edges[0]: Vertex 2 is out of range",
+        ),
+        (
+            small_json("\"LA\": 2").replace("[1]", "[-1]"),
+            "targets[0]: Vertex -1 is out of range",
+        ),
+        (
+            small_json("\"LA\": 2").replace("[100, 0]", "[1001, 0]"),
+            "coordinates[1]: (1001, 0) is out of range",
+        ),
+        (
+            small_json("\"LA\": 2").replace("[[0, 1]]", "[[0, 1, 1]]"),
+            "trailing characters at line 1 column 37",
+        ),
+        (
+            small_json("\"LA\": 2").replace("[[0, 1]]", "[[0]]"),
+            "invalid length 1, expected a tuple of size 2",
+        ),
+    ] {
+        let err = format!("{:#}", input_from_json(&json).unwrap_err());
+        assert!(err.contains(expected), "{}: {}", json, err);
+    }
+}
+
+#[test]
+fn deep_nesting_does_not_exhaust_the_stack() {
+    let depth = 100_000;
+    let nested = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+    // Ignored members are skipped without recursion
+    let json = small_json(&format!("\"x\": {}, \"LA\": 2", nested));
+    assert_eq!(input_from_json(&json).unwrap().al(), 2);
+
+    // Members read into the input stop at the first array where a number is expected
+    let json = small_json("\"LA\": 2").replace("[[0, 1]]", &format!("[{}]", nested));
+    let err = format!("{:#}", input_from_json(&json).unwrap_err());
+    assert!(
+        err.contains("invalid type: sequence, expected i64"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,66 @@
+use tools::{gen_with, validate_input, GenMode, GenParams, Graph};
+
+const SEEDS: u64 = 3;
+
+#[test]
+fn modes_yield_valid_inputs() {
+    for mode in GenMode::ALL {
+        assert_eq!(mode.to_string().parse(), Ok(mode));
+        for seed in 0..SEEDS {
+            let mut params = GenParams::default();
+            mode.apply(&mut params);
+            let (input, info) = gen_with(seed, &params).unwrap();
+            let violations = validate_input(&input);
+            assert!(violations.is_empty(), "{} {}: {:?}", mode, seed, violations);
+
+            match mode {
+                GenMode::MinLa => assert_eq!(info.al, info.n),
+                GenMode::MaxLa => assert_eq!(info.al, info.n * 2),
+                GenMode::MinLb => assert_eq!(info.bl, 4),
+                GenMode::MaxLb => assert_eq!(info.bl, 24),
+                GenMode::Tree => assert_eq!(info.m, info.n - 1),
+                GenMode::Dense => assert_eq!(info.edge_erasing_ratio, 0.0),
+                GenMode::FarTargets => {
+                    let g = Graph::new(&input);
+                    let mut now = 0;
+                    for &v in input.t() {
+                        let dist = g.bfs(now);
+                        let max_dist = *dist.iter().max().unwrap();
+                        assert!(dist[v] * 4 >= max_dist * 3);
+                        now = v;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn modes_keep_fixed_values() {
+    let mut params = GenParams {
+        n: Some(300),
+        bl: Some(9),
+        ..Default::default()
+    };
+    for mode in [GenMode::MaxLa, GenMode::MinLb] {
+        mode.apply(&mut params);
+    }
+    let (_, info) = gen_with(0, &params).unwrap();
+    assert_eq!((info.n, info.al, info.bl), (300, 600, 9));
+}
+
+#[test]
+fn seeds_reproduce_the_sample_inputs() {
+    // Any change to the generator must keep these files byte for byte
+    let seeds = std::fs::read_to_string("seeds.txt").unwrap();
+    let seeds = seeds
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse::<u64>().unwrap());
+    for (id, seed) in seeds.enumerate().take(5) {
+        let (input, _) = gen_with(seed, &GenParams::default()).unwrap();
+        let expected = std::fs::read_to_string(format!("in/{:04}.txt", id)).unwrap();
+        assert!(input.to_string() == expected, "seed {}", seed);
+    }
+}
@@ -0,0 +1,91 @@
+use tools::{compute_score, parse_input, parse_input_dsl, InputBuilder, OutputBuilder};
+
+// A square with a tail
+//   0 - 1
+//   |   |
+//   3 - 2
+//        \
+//         4
+const SQUARE: &str = "
+la 5
+lb 4
+v 0 0
+v 100 0
+v 100 100
+v 0 100
+v 200 200
+e 0 1 2 3 0
+e 2 4
+t 1 4 2 0
+";
+
+#[test]
+fn dsl_builds_a_valid_input() {
+    let input = parse_input_dsl(SQUARE).unwrap().build().unwrap();
+    assert_eq!((input.n(), input.m(), input.t_sz()), (5, 5, 4));
+    assert_eq!(input.edges(), [(0, 1), (1, 2), (2, 3), (3, 0), (2, 4)]);
+    assert_eq!(parse_input(&input.to_string()).unwrap().t(), [1, 4, 2, 0]);
+}
+
+#[test]
+fn generator_rules_are_checked() {
+    let mut builder = InputBuilder::new();
+    builder
+        .vertex(0, 0)
+        .vertex(10, 0)
+        .vertex(500, 500)
+        .edge(0, 1)
+        .edge(1, 1)
+        .target(0);
+    let err = builder.build().unwrap_err().to_string();
+    assert!(err.contains("self-loop"), "{}", err);
+    assert!(err.contains("closer than"), "{}", err);
+    assert!(err.contains("connected components"), "{}", err);
+    assert!(err.contains("same as the previous vertex"), "{}", err);
+
+    // The parser still accepts it
+    let input = builder.build_unchecked().unwrap();
+    assert!(parse_input(&input.to_string()).is_ok());
+}
+
+#[test]
+fn dsl_errors_have_line_numbers() {
+    let err = parse_input_dsl("v 0 0\ne 0\n").unwrap_err();
+    assert_eq!(format!("{:#}", err), "line 2: Usage: e V_1 V_2 ..");
+    let err = parse_input_dsl("v 0 0\n\nx 1\n").unwrap_err();
+    assert_eq!(format!("{:#}", err), "line 3: Unknown command: x");
+}
+
+#[test]
+fn overwritten_slots_of_b_turn_red() {
+    let input = parse_input_dsl(SQUARE).unwrap().build().unwrap();
+    let a = vec![1, 2, 4, 3, 0];
+
+    // B = [1, 2, 4, -1] -> [1, 2, 4, 3] -> [0, 2, 4, 3]
+    let mut builder = OutputBuilder::new();
+    builder
+        .set_a(a.clone())
+        .copy_signals(3, 0, 0)
+        .move_to(1)
+        .move_to(2)
+        .move_to(4)
+        .copy_signals(1, 3, 3)
+        .move_to(2)
+        .move_to(3)
+        .copy_signals(1, 4, 0)
+        .move_to(0);
+    let output = builder.build().to_string();
+    assert_eq!(compute_score(&input, &output), Ok(3));
+
+    // Vertex 1 is red again once B[0] is overwritten
+    let mut builder = OutputBuilder::new();
+    builder
+        .set_a(a)
+        .copy_signals(3, 0, 0)
+        .move_to(1)
+        .copy_signals(1, 4, 0)
+        .move_to(2)
+        .move_to(1);
+    let err = compute_score(&input, &builder.build().to_string()).unwrap_err();
+    assert_eq!(err.op_idx(), Some(4));
+}
@@ -0,0 +1,313 @@
+mod common;
+
+use common::{random_copy, random_valid_output};
+use rand::{seq::SliceRandom, Rng};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use tools::{
+    compute_score, gen_with, judge, judge_with, parse_input, CopySignals, GenParams, Graph, Input,
+    JudgeError, Observer, Op, OutputBuilder, Scorer, State, Timeline, Violation,
+};
+
+const CASES: u64 = 200;
+
+// A small input which still follows the rules of the generator
+fn small_input(seed: u64) -> Input {
+    let mut rng = ChaCha20Rng::seed_from_u64(seed);
+    let params = GenParams {
+        n: Some(rng.gen_range(5..=40)),
+        t_sz: Some(rng.gen_range(1..=20)),
+        edge_max_distance: Some(400),
+        ..Default::default()
+    };
+    gen_with(seed, &params).unwrap().0
+}
+
+// Checks the invariants of `State` after every operation
+#[derive(Default)]
+struct Checker {
+    copy_cnt: usize,
+    op_cnt: usize,
+}
+
+impl Checker {
+    fn check(&self, input: &Input, state: &State) {
+        let mut cnt = vec![0; input.n()];
+        for &v in state.b() {
+            assert!(v >= -1);
+            if v >= 0 {
+                cnt[v as usize] += 1;
+            }
+        }
+        assert_eq!(state.traffic_light(), cnt);
+        assert_eq!(state.score(), self.copy_cnt);
+        assert_eq!(state.op_cnt(), self.op_cnt);
+    }
+}
+
+impl Observer for Checker {
+    fn on_init(&mut self, input: &Input, state: &State, _comments: &[String]) {
+        assert_eq!(state.a().len(), input.al());
+        assert_eq!(state.b().len(), input.bl());
+        self.check(input, state);
+    }
+
+    fn on_copy_signals(
+        &mut self,
+        input: &Input,
+        state: &State,
+        _op_idx: usize,
+        cs: CopySignals,
+        _comments: &[String],
+    ) {
+        self.copy_cnt += 1;
+        self.op_cnt += 1;
+        for i in 0..cs.len {
+            assert_eq!(state.b()[cs.p_b + i], state.a()[cs.p_a + i] as isize);
+        }
+        self.check(input, state);
+    }
+
+    fn on_move(
+        &mut self,
+        input: &Input,
+        state: &State,
+        _op_idx: usize,
+        v: usize,
+        _comments: &[String],
+    ) {
+        self.op_cnt += 1;
+        assert_eq!(state.cur_v(), v);
+        assert!(state.traffic_light()[v] > 0);
+        self.check(input, state);
+    }
+
+    fn on_error(&mut self, input: &Input, state: &State, _error: &JudgeError) {
+        // The rejected operation must not have changed anything
+        self.check(input, state);
+    }
+}
+
+#[test]
+fn input_round_trip() {
+    for seed in 0..CASES {
+        let input = small_input(seed);
+        let text = input.to_string();
+        let parsed = parse_input(&text).unwrap();
+        assert_eq!(parsed.to_string(), text);
+        assert_eq!(parsed.edges(), input.edges());
+        assert_eq!(parsed.t(), input.t());
+        assert_eq!(parsed.coordinates(), input.coordinates());
+        assert_eq!(
+            (
+                parsed.n(),
+                parsed.m(),
+                parsed.t_sz(),
+                parsed.al(),
+                parsed.bl()
+            ),
+            (input.n(), input.m(), input.t_sz(), input.al(), input.bl())
+        );
+    }
+}
+
+#[test]
+fn score_is_the_number_of_copies() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for seed in 0..CASES {
+        let input = small_input(seed);
+        let (builder, copy_cnt) = random_valid_output(&mut rng, &input);
+        let output = builder.build().to_string();
+
+        let mut checker = Checker::default();
+        let mut scorer = Scorer::default();
+        let res = judge_with(&input, &output, 5, &mut [&mut checker, &mut scorer]).unwrap();
+        assert_eq!(res.score, copy_cnt);
+        assert_eq!(checker.copy_cnt, copy_cnt);
+        assert_eq!(scorer.score, copy_cnt);
+        assert_eq!(compute_score(&input, &output), Ok(copy_cnt));
+    }
+}
+
+#[test]
+fn traffic_light_follows_b_on_random_ops() {
+    // Not necessarily visiting the targets, so the replays end with TargetsNotVisited
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+    for seed in 0..CASES {
+        let input = small_input(seed);
+        let g = Graph::new(&input);
+        let a = (0..input.al())
+            .map(|_| rng.gen_range(0..input.n()))
+            .collect::<Vec<_>>();
+        let mut builder = OutputBuilder::new();
+        builder.set_a(a.clone());
+
+        let mut b = vec![usize::MAX; input.bl()];
+        let mut cur = 0;
+        for _ in 0..100 {
+            let green = g
+                .neighbors(cur)
+                .iter()
+                .copied()
+                .filter(|v| b.contains(v))
+                .collect::<Vec<_>>();
+            if green.is_empty() || rng.gen_bool(0.5) {
+                let cs = random_copy(&mut rng, &input);
+                builder.copy_signals(cs.len, cs.p_a, cs.p_b);
+                b[cs.p_b..cs.p_b + cs.len].copy_from_slice(&a[cs.p_a..cs.p_a + cs.len]);
+            } else {
+                cur = *green.choose(&mut rng).unwrap();
+                builder.move_to(cur);
+            }
+        }
+
+        let output = builder.build().to_string();
+        let mut checker = Checker::default();
+        match judge_with(&input, &output, 5, &mut [&mut checker]) {
+            Ok(_) | Err(JudgeError::TargetsNotVisited { .. }) => {}
+            Err(err) => panic!("{}", err),
+        }
+        assert_eq!(checker.op_cnt, 100);
+    }
+}
+
+// The operation `op_idx` of the output is rejected for `expected`, keeping the state as it was
+fn assert_rejected(input: &Input, builder: &OutputBuilder, op_idx: usize, expected: Violation) {
+    let output = builder.build().to_string();
+    let mut checker = Checker::default();
+    match judge_with(input, &output, 5, &mut [&mut checker]) {
+        Err(JudgeError::Op {
+            op_idx: i,
+            violation,
+            ..
+        }) => {
+            assert_eq!((i, violation), (op_idx, expected), "{}", output);
+        }
+        res => panic!("{:?} is accepted: {}", res.map(|res| res.score), output),
+    }
+    assert_eq!(checker.op_cnt, op_idx);
+}
+
+#[test]
+fn copy_signals_rejects_out_of_range() {
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+    for seed in 0..CASES {
+        let input = small_input(seed);
+        let (mut builder, _) = random_valid_output(&mut rng, &input);
+        let op_idx = builder.build().commented_ops().len();
+
+        // R_A sticks out of A; R_A is checked before R_B
+        let len = rng.gen_range(1..input.n()).min(input.al());
+        let p_a = rng.gen_range(input.al() + 1 - len..=input.al());
+        let p_b = rng.gen_range(0..=input.bl());
+        let mut over_a = builder.clone();
+        over_a.copy_signals(len, p_a, p_b);
+        assert_rejected(&input, &over_a, op_idx, Violation::RangeA { p_a, len });
+
+        // Only R_B sticks out of B
+        let len = rng.gen_range(1..=input.al().min(input.n() - 1));
+        let p_a = rng.gen_range(0..=input.al() - len);
+        let p_b = rng.gen_range(input.bl().saturating_sub(len) + 1..=input.bl());
+        builder.copy_signals(len, p_a, p_b);
+        assert_rejected(&input, &builder, op_idx, Violation::RangeB { p_b, len });
+    }
+}
+
+#[test]
+fn move_rejects_red_or_distant_vertices() {
+    let mut rng = ChaCha20Rng::seed_from_u64(3);
+    for seed in 0..CASES {
+        let input = small_input(seed);
+        let g = Graph::new(&input);
+        let (builder, _) = random_valid_output(&mut rng, &input);
+        let output = builder.build();
+        let op_idx = output.commented_ops().len();
+        let mut b = vec![usize::MAX; input.bl()];
+        for commented_op in output.commented_ops() {
+            if let Op::CopySignals(cs) = commented_op.op {
+                b[cs.p_b..cs.p_b + cs.len]
+                    .copy_from_slice(&output.initial_a()[cs.p_a..cs.p_a + cs.len]);
+            }
+        }
+        let cur = input.t().last().copied().unwrap_or(0);
+
+        for v in 0..input.n() {
+            let mut builder = builder.clone();
+            builder.move_to(v);
+            // The color is checked before the adjacency
+            if !b.contains(&v) {
+                let violation = Violation::RedVertex { from: cur, to: v };
+                assert_rejected(&input, &builder, op_idx, violation);
+            } else if !g.is_adjacent(cur, v) {
+                let violation = Violation::NotAdjacent { from: cur, to: v };
+                assert_rejected(&input, &builder, op_idx, violation);
+            }
+        }
+    }
+}
+
+#[test]
+fn other_rejections() {
+    let input = small_input(0);
+    let mut builder = OutputBuilder::new();
+    builder.set_a((0..input.al()).map(|i| i % input.n()).collect());
+    let output = builder.build().to_string();
+    assert_eq!(
+        compute_score(&input, &output),
+        Err(JudgeError::TargetsNotVisited {
+            target_idx: 0,
+            t_sz: input.t_sz()
+        })
+    );
+
+    for _ in 0..100_000 {
+        builder.copy_signals(1, 0, 0);
+    }
+    builder.copy_signals(1, input.al(), 0);
+    let output = builder.build().to_string();
+    let err = compute_score(&input, &output).unwrap_err();
+    assert_eq!(err.cause(), "too_many_ops");
+    assert_eq!(err.op_idx(), Some(100_000));
+}
+
+#[test]
+fn failures_carry_the_progress() {
+    let mut rng = ChaCha20Rng::seed_from_u64(4);
+    for seed in 0..CASES {
+        let input = small_input(seed);
+        let (mut builder, copy_cnt) = random_valid_output(&mut rng, &input);
+        let op_cnt = builder.build().commented_ops().len();
+        builder.copy_signals(1, input.al(), 0);
+        let output = builder.build().to_string();
+
+        let mut timeline = Timeline::new();
+        let mut input_res = Input::new();
+        let Err(failure) = judge(
+            &input.to_string(),
+            &output,
+            &mut timeline,
+            &mut input_res,
+            5,
+        ) else {
+            panic!("{} is accepted", output);
+        };
+        assert_eq!(failure.error.cause(), "range_a");
+        let progress = failure.progress.unwrap();
+        assert_eq!(progress.last_valid_turn, op_cnt);
+        assert_eq!(
+            (progress.target_idx, progress.t_sz),
+            (input.t_sz(), input.t_sz())
+        );
+        assert_eq!(progress.copy_cnt, copy_cnt);
+        assert_eq!(Some(progress), timeline.progress());
+    }
+
+    // Nothing was replayed
+    let input = small_input(0);
+    let mut timeline = Timeline::new();
+    let mut input_res = Input::new();
+    let Err(failure) = judge(&input.to_string(), "x", &mut timeline, &mut input_res, 5) else {
+        panic!("a broken output is accepted");
+    };
+    assert_eq!(failure.error.cause(), "parse");
+    assert_eq!(failure.progress, None);
+}
@@ -0,0 +1,99 @@
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use tools::{parse_input, parse_output, CopySignals, Input, Op, OutputBuilder};
+
+const INPUT: &str = "\
+5 5 3 6 4
+0 1
+1 2
+2 3
+3 4
+4 0
+2 4 1
+0 0
+100 0
+100 100
+0 100
+50 150
+";
+
+const CASES: usize = 1000;
+
+fn random_comment(rng: &mut ChaCha20Rng) -> String {
+    const CHARS: &[char] = &['a', 'z', '0', '#', ' ', '\t', '\n', '@', '"', 's', 'm'];
+    let len = rng.gen_range(0..12);
+    (0..len)
+        .map(|_| CHARS[rng.gen_range(0..CHARS.len())])
+        .collect()
+}
+
+// Any op accepted by the parser, not necessarily a valid one
+fn random_op(rng: &mut ChaCha20Rng, input: &Input) -> Op {
+    if rng.gen_bool(0.5) {
+        Op::CopySignals(CopySignals {
+            len: rng.gen_range(1..input.n()),
+            p_a: rng.gen_range(0..=input.al()),
+            p_b: rng.gen_range(0..=input.bl()),
+        })
+    } else {
+        Op::Move(rng.gen_range(0..input.n()))
+    }
+}
+
+fn random_builder(rng: &mut ChaCha20Rng, input: &Input) -> OutputBuilder {
+    let mut builder = OutputBuilder::new();
+    for _ in 0..rng.gen_range(0..3) {
+        builder.initial_comment(&random_comment(rng));
+    }
+    builder.set_a(
+        (0..input.al())
+            .map(|_| rng.gen_range(0..input.n()))
+            .collect(),
+    );
+    for _ in 0..rng.gen_range(0..30) {
+        for _ in 0..rng.gen_range(0..3) {
+            builder.comment(&random_comment(rng));
+        }
+        builder.push(random_op(rng, input));
+    }
+    for _ in 0..rng.gen_range(0..3) {
+        builder.comment(&random_comment(rng));
+    }
+    builder
+}
+
+#[test]
+fn output_round_trip() {
+    let input = parse_input(INPUT).unwrap();
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for _ in 0..CASES {
+        let output = random_builder(&mut rng, &input).build();
+        let text = output.to_string();
+        let parsed = parse_output(&text, &input).unwrap();
+        assert_eq!(parsed, output, "{}", text);
+        assert_eq!(parsed.to_string(), text);
+    }
+}
+
+#[test]
+fn comments_are_attached_like_the_parser() {
+    let input = parse_input(INPUT).unwrap();
+    let output = OutputBuilder::new()
+        .initial_comment("  before A  ")
+        .set_a(vec![0, 1, 2, 3, 4, 0])
+        .comment("first\nsecond")
+        .copy_signals(4, 0, 0)
+        .move_to(1)
+        .comment("")
+        .build();
+
+    assert_eq!(output.initial_comments(), ["before A"]);
+    assert_eq!(output.commented_ops()[0].comments, ["first", "second"]);
+    assert!(output.commented_ops()[1].comments.is_empty());
+    assert_eq!(output.trailing_comments(), [""]);
+    assert_eq!(
+        output.to_string(),
+        "# before A\n0 1 2 3 4 0\n# first\n# second\ns 4 0 0\nm 1\n#\n"
+    );
+    assert_eq!(parse_output(&output.to_string(), &input).unwrap(), output);
+}
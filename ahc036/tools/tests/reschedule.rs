@@ -0,0 +1,84 @@
+mod common;
+
+use common::{random_valid_output, SAMPLE_FILES};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use tools::{
+    compute_score, greedy_reschedule, parse_input, Input, InputBuilder, Op, Output, OutputBuilder,
+};
+
+fn moves(output: &Output) -> Vec<usize> {
+    output
+        .commented_ops()
+        .iter()
+        .filter_map(|commented_op| match commented_op.op {
+            Op::Move(v) => Some(v),
+            Op::CopySignals(_) => None,
+        })
+        .collect()
+}
+
+// Reschedule a valid output and check that it keeps A and the moves and is never worse
+fn check(input: &Input, output: &Output) -> (usize, usize) {
+    let old_score = compute_score(input, &output.to_string()).unwrap();
+    let rescheduled = greedy_reschedule(input, output).unwrap();
+    let new_score = compute_score(input, &rescheduled.to_string()).unwrap();
+    assert_eq!(rescheduled.initial_a(), output.initial_a());
+    assert_eq!(moves(&rescheduled), moves(output));
+    assert!(new_score <= old_score, "{} > {}", new_score, old_score);
+    (old_score, new_score)
+}
+
+#[test]
+fn triangle_needs_one_copy_per_vertex() {
+    // 0 - 1
+    //  \ /
+    //   2
+    let mut builder = InputBuilder::new();
+    builder
+        .vertex(0, 0)
+        .vertex(100, 0)
+        .vertex(0, 100)
+        .edge(0, 1)
+        .edge(1, 2)
+        .edge(2, 0)
+        .set_al(3)
+        .set_bl(2);
+    for _ in 0..3 {
+        builder.target(2).target(0);
+    }
+    let input = builder.build_unchecked().unwrap();
+
+    // No window of length 2 in A holds both 0 and 2, so full-width copies would be needed
+    // before every move, while copying each of them into its own slot once costs 2
+    let mut builder = OutputBuilder::new();
+    builder
+        .set_a(vec![0, 1, 2])
+        .copy_signals(1, 0, 0)
+        .copy_signals(1, 2, 1);
+    for _ in 0..3 {
+        builder.move_to(2).move_to(0);
+    }
+    assert_eq!(check(&input, &builder.build()), (2, 2));
+
+    // The same is found without the copies of the original
+    let mut builder = OutputBuilder::new();
+    builder.set_a(vec![0, 1, 2]);
+    for _ in 0..3 {
+        builder.move_to(2).move_to(0);
+    }
+    let rescheduled = greedy_reschedule(&input, &builder.build()).unwrap();
+    assert_eq!(compute_score(&input, &rescheduled.to_string()), Ok(2));
+}
+
+#[test]
+fn random_outputs_never_get_worse() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    for file in SAMPLE_FILES {
+        let input = parse_input(&std::fs::read_to_string(file).unwrap()).unwrap();
+        for _ in 0..3 {
+            let output = random_valid_output(&mut rng, &input).0.build();
+            let (old_score, new_score) = check(&input, &output);
+            assert!(new_score < old_score, "{}", file);
+        }
+    }
+}
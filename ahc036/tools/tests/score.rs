@@ -0,0 +1,69 @@
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use tools::{compute_score, judge, parse_input, solve, Input, Timeline};
+
+// A copy of `output_s` broken in one random way, though it may still happen to be valid
+fn corrupt(rng: &mut ChaCha20Rng, input: &Input, output_s: &str) -> String {
+    let mut lines = output_s
+        .lines()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>();
+    let i = rng.gen_range(0..lines.len());
+    match rng.gen_range(0..7) {
+        // drop a line
+        0 => {
+            lines.remove(i);
+        }
+        // replace a token with a number which may be out of range
+        1 => {
+            let mut tokens = lines[i].split_whitespace().collect::<Vec<_>>();
+            let j = rng.gen_range(0..tokens.len());
+            let val = rng.gen_range(0..input.n() + 30).to_string();
+            tokens[j] = &val;
+            lines[i] = tokens.join(" ");
+        }
+        // cut the output
+        2 => lines.truncate(i),
+        // move to a random vertex
+        3 => lines.insert(i, format!("m {}", rng.gen_range(0..input.n()))),
+        // an unknown operation
+        4 => lines.insert(i, "x 1".to_string()),
+        // an extra token
+        5 => lines[i].push_str(" 0"),
+        // a comment, which changes nothing
+        _ => lines.insert(i, "# comment".to_string()),
+    }
+    lines.join("\n")
+}
+
+#[test]
+fn compute_score_agrees_with_judge() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let mut files = std::fs::read_dir("in")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert!(!files.is_empty());
+
+    for file in files.iter() {
+        let input_s = std::fs::read_to_string(file).unwrap();
+        let input = parse_input(&input_s).unwrap();
+        let output_s = solve(&input).unwrap().to_string();
+
+        let mut outputs = vec![output_s.clone()];
+        for _ in 0..4 {
+            outputs.push(corrupt(&mut rng, &input, &output_s));
+        }
+        for output_s in outputs.iter() {
+            let mut timeline = Timeline::new();
+            let mut input_res = Input::new();
+            let expected = judge(&input_s, output_s, &mut timeline, &mut input_res, 10)
+                .map(|res| res.score)
+                .map_err(|failure| failure.error);
+            let actual = compute_score(&input, output_s);
+            assert_eq!(actual, expected, "{}", file.display());
+        }
+        assert!(compute_score(&input, &output_s).is_ok());
+    }
+}
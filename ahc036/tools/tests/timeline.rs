@@ -0,0 +1,111 @@
+mod common;
+
+use common::SAMPLE_FILES;
+use tools::{
+    judge_with, parse_input, solve, CopySignals, Input, Observer, Op, OutputBuilder, State,
+    Timeline,
+};
+
+// Keeps a full copy of the state of every turn, like `judge` did before `Timeline`
+#[derive(Default)]
+struct Recorder {
+    states: Vec<State>,
+}
+
+impl Observer for Recorder {
+    fn on_init(&mut self, _input: &Input, state: &State, _comments: &[String]) {
+        self.states = vec![state.clone()];
+    }
+
+    fn on_copy_signals(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        _cs: CopySignals,
+        _comments: &[String],
+    ) {
+        self.states.push(state.clone());
+    }
+
+    fn on_move(
+        &mut self,
+        _input: &Input,
+        state: &State,
+        _op_idx: usize,
+        _v: usize,
+        _comments: &[String],
+    ) {
+        self.states.push(state.clone());
+    }
+}
+
+fn assert_same_state(t: usize, actual: &State, expected: &State) {
+    assert_eq!(actual.score(), expected.score(), "turn {}", t);
+    assert_eq!(actual.op_cnt(), expected.op_cnt(), "turn {}", t);
+    assert_eq!(actual.target_idx(), expected.target_idx(), "turn {}", t);
+    assert_eq!(actual.cur_v(), expected.cur_v(), "turn {}", t);
+    assert_eq!(actual.a(), expected.a(), "turn {}", t);
+    assert_eq!(actual.b(), expected.b(), "turn {}", t);
+    assert_eq!(actual.visited(), expected.visited(), "turn {}", t);
+    assert_eq!(
+        actual.traffic_light(),
+        expected.traffic_light(),
+        "turn {}",
+        t
+    );
+}
+
+// Replay `output_s` and compare every turn of the timeline with the recorded states
+fn check_every_turn(input: &Input, output_s: &str, k: usize) -> usize {
+    let mut timeline = Timeline::new();
+    let mut recorder = Recorder::default();
+    let _ = judge_with(input, output_s, k, &mut [&mut timeline, &mut recorder]);
+
+    assert_eq!(timeline.len(), recorder.states.len());
+    for (t, expected) in recorder.states.iter().enumerate() {
+        let vis_data = timeline.get(t).unwrap();
+        assert_same_state(t, vis_data.state(), expected);
+    }
+    assert!(timeline.get(timeline.len()).is_none());
+    timeline.len()
+}
+
+#[test]
+fn timeline_matches_every_turn() {
+    for file in SAMPLE_FILES {
+        let input = parse_input(&std::fs::read_to_string(file).unwrap()).unwrap();
+        let output = solve(&input).unwrap();
+        for k in [0, 1, 10] {
+            let turns = check_every_turn(&input, &output.to_string(), k);
+            assert_eq!(turns, output.commented_ops().len() + 1);
+        }
+    }
+}
+
+#[test]
+fn timeline_matches_every_turn_until_an_error() {
+    for file in SAMPLE_FILES {
+        let input = parse_input(&std::fs::read_to_string(file).unwrap()).unwrap();
+        let output = solve(&input).unwrap();
+
+        // Dropping a copy operation in the middle makes a later move fail
+        let ops = output.commented_ops();
+        let dropped = ops
+            .iter()
+            .enumerate()
+            .filter(|(_, commented_op)| matches!(commented_op.op, Op::CopySignals(_)))
+            .map(|(i, _)| i)
+            .nth(10)
+            .unwrap();
+        let mut builder = OutputBuilder::new();
+        builder.set_a(output.initial_a().to_vec());
+        for (i, commented_op) in ops.iter().enumerate() {
+            if i != dropped {
+                builder.push(commented_op.op);
+            }
+        }
+        let turns = check_every_turn(&input, &builder.build().to_string(), 10);
+        assert!(turns > dropped && turns < ops.len());
+    }
+}
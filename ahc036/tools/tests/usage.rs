@@ -0,0 +1,110 @@
+use tools::{judge_with, parse_input_dsl, OutputBuilder, SignalUsage};
+
+// A square with a tail
+//   0 - 1
+//   |   |
+//   3 - 2
+//        \
+//         4
+const SQUARE: &str = "
+la 5
+lb 4
+v 0 0
+v 100 0
+v 100 100
+v 0 100
+v 200 200
+e 0 1 2 3 0
+e 2 4
+t 1 4 2 0 3 0
+";
+
+#[test]
+fn moves_are_credited_to_the_latest_slot() {
+    let input = parse_input_dsl(SQUARE).unwrap().build().unwrap();
+    let mut builder = OutputBuilder::new();
+    builder
+        .set_a(vec![1, 2, 4, 3, 0])
+        // B = [1, 2, 4, -1]
+        .copy_signals(3, 0, 0)
+        .move_to(1)
+        .move_to(2)
+        .move_to(4)
+        // B = [1, 2, 4, -1], rewriting 2 and 4 without turning them green
+        .copy_signals(2, 1, 1)
+        .move_to(2)
+        // B = [1, 2, 3, 0], overwriting 4
+        .copy_signals(2, 3, 2)
+        .move_to(3)
+        // B = [0, 2, 3, 0], where 0 is held twice and the newer slot gets the moves
+        .copy_signals(1, 4, 0)
+        .move_to(0)
+        .move_to(3)
+        .move_to(0);
+
+    let mut usage = SignalUsage::new();
+    judge_with(&input, &builder.build().to_string(), 0, &mut [&mut usage]).unwrap();
+
+    let copies = &usage.copies;
+    assert_eq!(
+        copies.iter().map(|copy| copy.op_idx).collect::<Vec<_>>(),
+        [0, 4, 6, 8]
+    );
+    assert_eq!(
+        copies
+            .iter()
+            .map(|copy| copy.values.clone())
+            .collect::<Vec<_>>(),
+        [vec![1, 2, 4], vec![2, 4], vec![3, 0], vec![0]]
+    );
+    assert_eq!(
+        copies
+            .iter()
+            .map(|copy| copy.slot_moves.clone())
+            .collect::<Vec<_>>(),
+        [vec![1, 1, 1], vec![1, 0], vec![2, 0], vec![2]]
+    );
+    assert_eq!(
+        copies
+            .iter()
+            .map(|copy| copy.turned_green.clone())
+            .collect::<Vec<_>>(),
+        [vec![1, 2, 4], vec![], vec![3, 0], vec![]]
+    );
+    assert_eq!(
+        copies
+            .iter()
+            .map(|copy| (copy.wasted_slots(), copy.unused_green()))
+            .collect::<Vec<_>>(),
+        [(0, 0), (1, 0), (1, 1), (0, 0)]
+    );
+
+    assert_eq!(usage.moves(), 8);
+    assert_eq!(usage.slots_written(), 8);
+    assert_eq!(usage.wasted_slots(), 2);
+    assert_eq!(usage.turned_green(), 5);
+    assert_eq!(usage.unused_green(), 1);
+}
+
+#[test]
+fn usage_stops_at_the_error() {
+    let input = parse_input_dsl(SQUARE).unwrap().build().unwrap();
+    let mut builder = OutputBuilder::new();
+    builder
+        .set_a(vec![1, 2, 4, 3, 0])
+        .copy_signals(2, 0, 0)
+        .move_to(1)
+        // B[0] = 0 turns 1 red, and 2 is never moved to
+        .copy_signals(1, 4, 0)
+        .move_to(0)
+        // 3 is red
+        .move_to(3);
+
+    let mut usage = SignalUsage::new();
+    assert!(judge_with(&input, &builder.build().to_string(), 0, &mut [&mut usage]).is_err());
+    assert_eq!(usage.copies.len(), 2);
+    assert_eq!(usage.copies[0].slot_moves, [1, 0]);
+    assert_eq!(usage.copies[1].slot_moves, [1]);
+    assert_eq!(usage.wasted_slots(), 1);
+    assert_eq!(usage.unused_green(), 1);
+}
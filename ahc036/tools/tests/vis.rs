@@ -0,0 +1,57 @@
+mod common;
+
+use common::{random_valid_output, SAMPLE_FILES};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use tools::{
+    draw_background, draw_frame, draw_svg_with, judge, parse_input, Graph, Input, Timeline,
+    VisOptions,
+};
+
+#[test]
+fn frames_over_the_background_give_the_full_drawing() {
+    let mut rng = ChaCha20Rng::seed_from_u64(0);
+    let input = parse_input(&std::fs::read_to_string(SAMPLE_FILES[0]).unwrap()).unwrap();
+    let (mut builder, _) = random_valid_output(&mut rng, &input);
+    // The directives of the last copy are drawn on the last turn
+    builder
+        .comment("@text a < b")
+        .comment("@mark v 0 red")
+        .copy_signals(1, 0, 0);
+    let output = builder.build().to_string();
+
+    let mut timeline = Timeline::new();
+    let mut input_res = Input::new();
+    judge(
+        &input.to_string(),
+        &output,
+        &mut timeline,
+        &mut input_res,
+        5,
+    )
+    .unwrap();
+
+    for options in [
+        VisOptions::default(),
+        VisOptions {
+            shortest_path: Some(Graph::new(&input)),
+            ..Default::default()
+        },
+    ] {
+        let background = draw_background(&timeline.get(0).unwrap(), &input, &options).to_string();
+        for t in (0..timeline.len()).step_by(499).chain([timeline.len() - 1]) {
+            let vis_data = timeline.get(t).unwrap();
+            let frame = draw_frame(&vis_data, &input, 5, &options);
+            // Put in place of the group, only the line breaks between the elements differ
+            assert_eq!(
+                background
+                    .replacen("<g id=\"frame\"/>", &frame, 1)
+                    .replace('\n', ""),
+                draw_svg_with(&vis_data, &input, 5, &options)
+                    .to_string()
+                    .replace('\n', ""),
+                "turn {}",
+                t
+            );
+        }
+    }
+}